@@ -0,0 +1,9 @@
+//! Static library wrapping poseidon with the C-interface declared in
+//! `include/poseidon.h`, built as `libposeidon.a`.
+//!
+//! The functions are those of `poseidon::ffi`, which the static library
+//! exports along with the rest of the crate.
+
+#![no_std]
+
+pub use poseidon_rs::ffi::*;
@@ -1,14 +1,176 @@
 #ifndef POSEIDON_H
 #define POSEIDON_H
 
+#include <stddef.h>
 #include <stdint.h>
 
 #ifdef __cplusplus
 extern "C" {
 #endif
 
-size_t c_hash_s128b(uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
-size_t c_hash_sw2(uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+/*
+ * Field elements are exchanged as the 32 bytes of their little-endian
+ * representation, and must be lower than the modulus.
+ *
+ * Functions never abort on invalid arguments: they return POSEIDON_OK or one of
+ * the error codes below, leaving their outputs untouched on error.
+ *
+ * c_hash_<params> hashes input, whose number of elements must be a positive
+ * multiple of the rate, into the first c_output_size_<params>() bytes of
+ * output. c_hash_be_<params> does the same with elements encoded as 32-byte
+ * big-endian integers, as EVM words and abi.encode(uint256[]) data.
+ * c_hash_batch_<params> hashes the input_len / message_len consecutive messages
+ * of message_len bytes each, writing their hash values one after the other to
+ * output.
+ * c_permute_<params> permutes a state of rate + capacity elements in place.
+ * c_output_size_<params> returns the length of a hash value in bytes.
+ */
+
+#define POSEIDON_OK 0
+/* A pointer argument is null. */
+#define POSEIDON_ERROR_NULL_POINTER 1
+/* A length is not a multiple of 32 bytes, an input is empty or not a multiple
+ * of the rate, or a state is not of rate + capacity elements. */
+#define POSEIDON_ERROR_LENGTH 2
+/* An element is not lower than the modulus. */
+#define POSEIDON_ERROR_NON_CANONICAL 3
+/* The output buffer is shorter than the output. */
+#define POSEIDON_ERROR_OUTPUT_TOO_SMALL 4
+/* The precompile input holds invalid parameters. */
+#define POSEIDON_ERROR_INVALID_PARAMETERS 5
+/* The precompile call exceeds its gas limit. */
+#define POSEIDON_ERROR_OUT_OF_GAS 6
+/* No set of parameters has the given identifier. */
+#define POSEIDON_ERROR_UNKNOWN_PARAMS 7
+
+int32_t c_hash_s128b(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_s128b(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_s128b(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_s128b(uint8_t *state, size_t state_len);
+size_t c_output_size_s128b(void);
+
+int32_t c_hash_sw2(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_sw2(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_sw2(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_sw2(uint8_t *state, size_t state_len);
+size_t c_output_size_sw2(void);
+
+int32_t c_hash_sw3(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_sw3(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_sw3(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_sw3(uint8_t *state, size_t state_len);
+size_t c_output_size_sw3(void);
+
+int32_t c_hash_sw4(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_sw4(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_sw4(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_sw4(uint8_t *state, size_t state_len);
+size_t c_output_size_sw4(void);
+
+int32_t c_hash_sw8(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_sw8(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_sw8(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_sw8(uint8_t *state, size_t state_len);
+size_t c_output_size_sw8(void);
+
+int32_t c_hash_pallas(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_pallas(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_pallas(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_pallas(uint8_t *state, size_t state_len);
+size_t c_output_size_pallas(void);
+
+int32_t c_hash_vesta(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_vesta(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_vesta(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_vesta(uint8_t *state, size_t state_len);
+size_t c_output_size_vesta(void);
+
+int32_t c_hash_poseidon2_pallas(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_poseidon2_pallas(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_poseidon2_pallas(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_poseidon2_pallas(uint8_t *state, size_t state_len);
+size_t c_output_size_poseidon2_pallas(void);
+
+int32_t c_hash_poseidon2_vesta(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_poseidon2_vesta(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_poseidon2_vesta(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_poseidon2_vesta(uint8_t *state, size_t state_len);
+size_t c_output_size_poseidon2_vesta(void);
+
+int32_t c_hash_poseidon2_stark252(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_be_poseidon2_stark252(const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len);
+int32_t c_hash_batch_poseidon2_stark252(const uint8_t *input, size_t input_len, size_t message_len, uint8_t *output, size_t output_len);
+int32_t c_permute_poseidon2_stark252(uint8_t *state, size_t state_len);
+size_t c_output_size_poseidon2_stark252(void);
+
+/*
+ * Sets of parameters by identifier, from 0 to poseidon_params_count() - 1.
+ * poseidon_get_params_info writes the shape of a set to info, field_size being
+ * the number of bytes of an element and name a static NUL-terminated string.
+ * poseidon_permute permutes a state in place as c_permute_<params>.
+ * poseidon_version returns the version of the library, as a static
+ * NUL-terminated string.
+ */
+
+#define POSEIDON_PARAMS_S128B 0
+#define POSEIDON_PARAMS_SW2 1
+#define POSEIDON_PARAMS_SW3 2
+#define POSEIDON_PARAMS_SW4 3
+#define POSEIDON_PARAMS_SW8 4
+#define POSEIDON_PARAMS_PALLAS 5
+#define POSEIDON_PARAMS_VESTA 6
+#define POSEIDON_PARAMS_POSEIDON2_PALLAS 7
+#define POSEIDON_PARAMS_POSEIDON2_VESTA 8
+#define POSEIDON_PARAMS_POSEIDON2_STARK252 9
+
+typedef struct poseidon_params_info {
+    const char *name;
+    size_t rate;
+    size_t capacity;
+    size_t output_size;
+    size_t field_size;
+} poseidon_params_info;
+
+uint32_t poseidon_params_count(void);
+int32_t poseidon_get_params_info(uint32_t param_id, poseidon_params_info *info);
+int32_t poseidon_permute(uint32_t param_id, uint8_t *state, size_t state_len);
+const char *poseidon_version(void);
+
+/*
+ * Streaming hashing. poseidon_sponge_new returns a sponge for one of the
+ * POSEIDON_PARAMS_* sets, or NULL for an unknown or Poseidon2 set, to be
+ * released with poseidon_sponge_free. poseidon_sponge_absorb and
+ * poseidon_sponge_squeeze take whole numbers of elements; absorbing a message
+ * whose length is a multiple of the rate then squeezing the output size gives
+ * the same hash value as c_hash_<params>. poseidon_sponge_clone returns a copy
+ * of a sponge, or NULL if sponge is NULL.
+ */
+
+typedef struct poseidon_sponge poseidon_sponge;
+
+poseidon_sponge *poseidon_sponge_new(uint32_t param_id);
+poseidon_sponge *poseidon_sponge_clone(const poseidon_sponge *sponge);
+int32_t poseidon_sponge_absorb(poseidon_sponge *sponge, const uint8_t *input, size_t input_len);
+int32_t poseidon_sponge_squeeze(poseidon_sponge *sponge, uint8_t *output, size_t output_len);
+void poseidon_sponge_free(poseidon_sponge *sponge);
+
+/*
+ * EIP-5988 precompile. c_precompile_required_gas writes the gas cost of a call
+ * to gas. c_precompile_run writes the hash value to output, its length to
+ * output_written and the gas used to gas_used.
+ */
+int32_t c_precompile_required_gas(const uint8_t *input, size_t input_len, uint64_t *gas);
+int32_t c_precompile_run(const uint8_t *input, size_t input_len, uint64_t gas_limit, uint8_t *output, size_t output_len, size_t *output_written, uint64_t *gas_used);
+
+/*
+ * Hashing with parameters supplied at runtime, laid out as the input of the
+ * precompile without its message: modulus, power, rate, capacity, output size,
+ * numbers of full and partial rounds, MDS matrix and round constants. The
+ * parameters are fully validated. input and output are made of 32-byte
+ * big-endian words, like parameters; the length of the hash value is written
+ * to output_written.
+ */
+int32_t c_hash_with_parameters(const uint8_t *parameters, size_t parameters_len, const uint8_t *input, size_t input_len, uint8_t *output, size_t output_len, size_t *output_written);
 
 #ifdef __cplusplus
 }
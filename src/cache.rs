@@ -0,0 +1,321 @@
+//! Cache of parameters generated on the fly.
+//!
+//! Parameters of unusual shapes are generated with the standard algorithm of
+//! the Poseidon reference implementation: a Grain LFSR seeded with the field
+//! size, the width and the numbers of rounds yields the round constants, then
+//! the MDS matrix is sampled from the same LFSR among Cauchy matrices passing
+//! [`crate::mds::is_secure`]. Generation is deterministic but costly, so a
+//! [`ParametersCache`] keeps the parameters of the shapes already requested,
+//! along with their validated prepared parameters, and reports whether a lookup
+//! had to generate them, for example to charge for it. Shapes are checked
+//! before generation, so that lookups with untrusted keys fail instead of
+//! panicking.
+//!
+//! A cache holds parameters of a single field `GF`, which stands for the
+//! modulus in the key (p, t, R_F, R_P, α).
+//!
+//! ```
+//! use poseidon::cache::{ParametersCache, ParametersKey};
+//! use poseidon::parameters::pallas::GF;
+//! let mut cache = ParametersCache::<GF>::new();
+//! let key = ParametersKey {
+//!     power: 5,
+//!     width: 3,
+//!     n_full_rounds: 8,
+//!     n_partial_rounds: 56,
+//! };
+//! let (_, generated) = cache.get(key).unwrap();
+//! assert!(generated);
+//! let (prepared, generated) = cache.get_prepared(key, 2, 1).unwrap();
+//! assert!(!generated);
+//! assert_eq!(prepared.rate(), 2);
+//! ```
+
+use crate::grain::Grain;
+use crate::mds::generate_mds_matrix;
+use crate::parameters::{is_valid_power, ParametersBuilder, ParametersError, PreparedParameters};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use ff::PrimeField;
+
+/// Widest shape generated, above the widths of the shipped sets, 9 at most.
+/// Generating and checking an MDS matrix costs more than cubic time in the
+/// width, so wider keys are rejected before any work.
+pub const MAX_WIDTH: usize = 16;
+
+/// Shape of a set of parameters over a given field.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ParametersKey {
+    pub power: u8,
+    pub width: usize,
+    pub n_full_rounds: usize,
+    pub n_partial_rounds: usize,
+}
+
+impl ParametersKey {
+    /// Checks that constants can be generated for the shape over the field
+    /// `GF`, without splitting the width into rate and capacity. The width
+    /// must be in [2, [`MAX_WIDTH`]].
+    pub fn validate<GF>(&self) -> Result<(), ParametersError>
+    where
+        GF: PrimeField,
+    {
+        if self.width < 2
+            || self.width > MAX_WIDTH
+            || self.n_full_rounds >= 1 << 10
+            || self.n_partial_rounds >= 1 << 10
+            || GF::NUM_BITS >= 1 << 12
+        {
+            return Err(ParametersError::UnsupportedShape);
+        }
+        if !is_valid_power::<GF>(self.power) {
+            return Err(ParametersError::InvalidPower { power: self.power });
+        }
+        Ok(())
+    }
+}
+
+/// Constants generated for a [`ParametersKey`].
+#[derive(Clone, Debug)]
+pub struct GeneratedParameters<GF> {
+    key: ParametersKey,
+    mds_matrix: Vec<GF>,
+    round_constants: Vec<GF>,
+}
+
+impl<GF> GeneratedParameters<GF>
+where
+    GF: PrimeField,
+{
+    /// Generates the constants of a shape, checked with
+    /// [`ParametersKey::validate`] beforehand.
+    pub fn generate(key: ParametersKey) -> Result<Self, ParametersError> {
+        key.validate::<GF>()?;
+        let mut grain = Grain::new(
+            GF::NUM_BITS as usize,
+            key.width,
+            key.n_full_rounds,
+            key.n_partial_rounds,
+        );
+        let round_constants = (0..(key.n_full_rounds + key.n_partial_rounds) * key.width)
+            .map(|_| grain.next_field_element())
+            .collect();
+        let mds_matrix = generate_mds_matrix(&mut grain, key.width);
+        Ok(GeneratedParameters {
+            key,
+            mds_matrix,
+            round_constants,
+        })
+    }
+
+    pub fn key(&self) -> ParametersKey {
+        self.key
+    }
+
+    pub fn mds_matrix(&self) -> &[GF] {
+        &self.mds_matrix
+    }
+
+    pub fn round_constants(&self) -> &[GF] {
+        &self.round_constants
+    }
+
+    /// Builds validated parameters splitting the width into `rate` and the
+    /// capacity.
+    pub fn prepare(
+        &self,
+        rate: usize,
+        output_size: usize,
+    ) -> Result<PreparedParameters<GF>, ParametersError> {
+        ParametersBuilder::new()
+            .power(self.key.power)
+            .rate(rate)
+            .capacity(self.key.width.saturating_sub(rate))
+            .output_size(output_size)
+            .n_full_rounds(self.key.n_full_rounds)
+            .n_partial_rounds(self.key.n_partial_rounds)
+            .build_prepared(self.mds_matrix.clone(), self.round_constants.clone())
+    }
+}
+
+/// Generated parameters of the shapes already requested.
+///
+/// Lookups take `&mut self`; callers sharing a cache between threads wrap it
+/// in their own lock.
+#[derive(Clone, Debug, Default)]
+pub struct ParametersCache<GF> {
+    entries: BTreeMap<ParametersKey, GeneratedParameters<GF>>,
+    // Prepared parameters by shape, rate and output size.
+    prepared: BTreeMap<(ParametersKey, usize, usize), PreparedParameters<GF>>,
+}
+
+impl<GF> ParametersCache<GF>
+where
+    GF: PrimeField,
+{
+    pub fn new() -> Self {
+        ParametersCache {
+            entries: BTreeMap::new(),
+            prepared: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the parameters of a shape, generating them if not cached, and
+    /// whether they were generated.
+    ///
+    /// Fails as [`GeneratedParameters::generate`], without caching anything.
+    pub fn get(
+        &mut self,
+        key: ParametersKey,
+    ) -> Result<(&GeneratedParameters<GF>, bool), ParametersError> {
+        if !self.entries.contains_key(&key) {
+            let params = GeneratedParameters::generate(key)?;
+            return Ok((self.entries.entry(key).or_insert(params), true));
+        }
+        Ok((&self.entries[&key], false))
+    }
+
+    /// Returns the prepared parameters of a shape split into `rate` and the
+    /// capacity, generating and validating them if not cached, and whether
+    /// the constants of the shape were generated.
+    ///
+    /// Validation happens once per shape, rate and output size.
+    pub fn get_prepared(
+        &mut self,
+        key: ParametersKey,
+        rate: usize,
+        output_size: usize,
+    ) -> Result<(&PreparedParameters<GF>, bool), ParametersError> {
+        let prepared_key = (key, rate, output_size);
+        if self.prepared.contains_key(&prepared_key) {
+            return Ok((&self.prepared[&prepared_key], false));
+        }
+        let (params, generated) = self.get(key)?;
+        let prepared = params.prepare(rate, output_size)?;
+        Ok((
+            self.prepared.entry(prepared_key).or_insert(prepared),
+            generated,
+        ))
+    }
+
+    /// Returns the parameters of a shape if cached.
+    pub fn get_cached(&self, key: &ParametersKey) -> Option<&GeneratedParameters<GF>> {
+        self.entries.get(key)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test_cache {
+    use super::*;
+    use crate::grain::round_constants;
+    use crate::mds::is_secure;
+    use crate::parameters::pallas::GF;
+
+    const KEY: ParametersKey = ParametersKey {
+        power: 5,
+        width: 3,
+        n_full_rounds: 8,
+        n_partial_rounds: 56,
+    };
+
+    #[test]
+    fn test_generate() {
+        let params = GeneratedParameters::<GF>::generate(KEY).unwrap();
+        assert_eq!(params.round_constants(), round_constants::<GF>(3, 8, 56));
+        assert!(is_secure(params.mds_matrix(), 3));
+        let prepared = params.prepare(2, 1).unwrap();
+        assert_eq!(prepared.mds_matrix(), params.mds_matrix());
+        assert_eq!(prepared.capacity(), 1);
+        assert_eq!(
+            params.prepare(3, 1).err(),
+            Some(ParametersError::ZeroCapacity)
+        );
+    }
+
+    #[test]
+    fn test_cache() {
+        let mut cache = ParametersCache::<GF>::new();
+        assert!(cache.get_cached(&KEY).is_none());
+        let (first, generated) = cache.get(KEY).unwrap();
+        assert!(generated);
+        let first = first.round_constants().to_vec();
+        let (second, generated) = cache.get(KEY).unwrap();
+        assert!(!generated);
+        assert_eq!(second.round_constants(), first);
+
+        let other = ParametersKey {
+            n_partial_rounds: 57,
+            ..KEY
+        };
+        let (third, generated) = cache.get(other).unwrap();
+        assert!(generated);
+        assert_eq!(third.key(), other);
+        assert_ne!(third.round_constants()[..3], first[..3]);
+        assert_eq!(cache.len(), 2);
+    }
+
+    #[test]
+    fn test_prepared() {
+        let mut cache = ParametersCache::<GF>::new();
+        let (prepared, generated) = cache.get_prepared(KEY, 2, 1).unwrap();
+        assert!(generated);
+        assert_eq!(prepared.capacity(), 1);
+        let first: *const _ = prepared;
+        let (prepared, generated) = cache.get_prepared(KEY, 2, 1).unwrap();
+        assert!(!generated);
+        assert_eq!(first, prepared as *const _);
+        let (_, generated) = cache.get_prepared(KEY, 1, 1).unwrap();
+        assert!(!generated);
+        assert_eq!(
+            cache.get_prepared(KEY, 3, 1).err(),
+            Some(ParametersError::ZeroCapacity)
+        );
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_keys() {
+        let mut cache = ParametersCache::<GF>::new();
+        let invalid = [
+            (
+                ParametersKey { width: 1, ..KEY },
+                ParametersError::UnsupportedShape,
+            ),
+            (
+                ParametersKey {
+                    width: MAX_WIDTH + 1,
+                    ..KEY
+                },
+                ParametersError::UnsupportedShape,
+            ),
+            (
+                ParametersKey {
+                    n_partial_rounds: 1 << 10,
+                    ..KEY
+                },
+                ParametersError::UnsupportedShape,
+            ),
+            (
+                ParametersKey { power: 3, ..KEY },
+                ParametersError::InvalidPower { power: 3 },
+            ),
+        ];
+        for (key, error) in invalid {
+            assert_eq!(cache.get(key).err(), Some(error));
+        }
+        assert!(cache.is_empty());
+        let widest = ParametersKey {
+            width: MAX_WIDTH,
+            ..KEY
+        };
+        assert_eq!(widest.validate::<GF>(), Ok(()));
+    }
+}
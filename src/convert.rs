@@ -1,4 +1,4 @@
-use alloc::vec::Vec;
+use alloc::{string::String, vec::Vec};
 use ff::PrimeField;
 
 pub fn felts_from_str<GF>(constants: &[&'static str]) -> Vec<GF>
@@ -12,6 +12,77 @@ where
     result
 }
 
+/// Returns the little-endian bytes of the integer representing an element,
+/// whatever the endianness of the field's representation.
+pub(crate) fn le_bytes<GF>(felt: &GF) -> Vec<u8>
+where
+    GF: PrimeField,
+{
+    let mut bytes = felt.to_repr().as_ref().to_vec();
+    if GF::ONE.to_repr().as_ref()[0] != 1 {
+        bytes.reverse();
+    }
+    bytes
+}
+
+/// Returns the big-endian bytes of the integer representing an element, as in
+/// an EVM word for 32-byte representations.
+pub(crate) fn be_bytes<GF>(felt: &GF) -> Vec<u8>
+where
+    GF: PrimeField,
+{
+    let mut bytes = le_bytes(felt);
+    bytes.reverse();
+    bytes
+}
+
+/// Builds the representation of the integer given in big-endian bytes, which
+/// must be as many as the bytes of a representation.
+pub(crate) fn repr_from_be_bytes<GF>(bytes: &[u8]) -> GF::Repr
+where
+    GF: PrimeField,
+{
+    let mut repr = GF::Repr::default();
+    repr.as_mut().copy_from_slice(bytes);
+    if GF::ONE.to_repr().as_ref()[0] == 1 {
+        repr.as_mut().reverse();
+    }
+    repr
+}
+
+/// Converts field elements to decimal strings, the inverse of
+/// [`felts_from_str`].
+pub fn str_from_felts<GF>(felts: &[GF]) -> Vec<String>
+where
+    GF: PrimeField,
+{
+    felts
+        .iter()
+        .map(|felt| decimal_from_le_bytes(&le_bytes(felt)))
+        .collect()
+}
+
+/// Converts an integer given in little-endian bytes to a decimal string.
+pub(crate) fn decimal_from_le_bytes(bytes: &[u8]) -> String {
+    let mut be_bytes = bytes.to_vec();
+    be_bytes.reverse();
+    let mut digits = Vec::new();
+    while be_bytes.iter().any(|&b| b != 0) {
+        let mut remainder = 0u32;
+        for b in be_bytes.iter_mut() {
+            let current = (remainder << 8) | *b as u32;
+            *b = (current / 10) as u8;
+            remainder = current % 10;
+        }
+        digits.push(b'0' + remainder as u8);
+    }
+    if digits.is_empty() {
+        digits.push(b'0');
+    }
+    digits.reverse();
+    String::from_utf8(digits).unwrap()
+}
+
 pub fn scalar_from_u8s<GF>(parts: &[u8]) -> GF
 where
     GF: PrimeField,
@@ -55,6 +126,44 @@ where
     output
 }
 
+/// Reads an element from the big-endian bytes of its integer, such as an EVM
+/// word.
+pub fn scalar_from_be_u8s<GF>(parts: &[u8]) -> GF
+where
+    GF: PrimeField,
+{
+    GF::from_repr_vartime(repr_from_be_bytes::<GF>(parts)).expect("u8s exceeds field modulus")
+}
+
+/// Reads elements from consecutive big-endian integers, such as the data of
+/// `abi.encode(uint256[])` without its offset and length words.
+pub fn felts_from_be_u8s<GF>(parts: &[u8]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let n_bytes = GF::ZERO.to_repr().as_ref().len();
+    if !parts.len().is_multiple_of(n_bytes) {
+        panic!(
+            "Incorrect length for felts: {} is not a multiple of {}",
+            parts.len(),
+            n_bytes
+        );
+    }
+    parts
+        .chunks_exact(n_bytes)
+        .map(scalar_from_be_u8s)
+        .collect()
+}
+
+/// Writes elements as consecutive big-endian integers, the inverse of
+/// [`felts_from_be_u8s`].
+pub fn be_u8s_from_felts<GF>(state: &[GF]) -> Vec<u8>
+where
+    GF: PrimeField,
+{
+    state.iter().flat_map(be_bytes).collect()
+}
+
 pub fn scalar_from_u64s<GF>(parts: &[u64]) -> GF
 where
     GF: PrimeField,
@@ -0,0 +1,729 @@
+//! C-Interface of the crate, declared in `include/poseidon.h`.
+//!
+//! Functions validate their arguments and never panic on invalid ones: they
+//! return [`POSEIDON_OK`] or one of the `POSEIDON_ERROR_*` codes, outputs being
+//! left untouched on error. Field elements are exchanged as the 32 bytes of
+//! their little-endian representation, and must be lower than the modulus.
+//!
+//! For each set of parameters:
+//! - `c_hash_<params>` hashes `input`, whose number of elements must be a
+//!   positive multiple of the rate, into the first `c_output_size_<params>()`
+//!   bytes of `output`.
+//! - `c_hash_be_<params>` does the same with elements encoded as 32-byte
+//!   big-endian integers, as EVM words and `abi.encode(uint256[])` data.
+//! - `c_hash_batch_<params>` hashes consecutive messages of `message_len`
+//!   bytes each, writing their hash values one after the other to `output`,
+//!   see [`crate::hash_batch`].
+//! - `c_permute_<params>` permutes in place a state of rate + capacity
+//!   elements.
+//! - `c_output_size_<params>` returns the length of a hash value in bytes.
+//!
+//! Sets are also identified by the `POSEIDON_PARAMS_*` numbers, from 0 to
+//! `poseidon_params_count() - 1`: `poseidon_get_params_info` describes a set
+//! and `poseidon_permute` runs its permutation.
+//!
+//! Messages can also be hashed incrementally with a [`PoseidonSponge`] handle,
+//! created by `poseidon_sponge_new` for one of the Poseidon sets and released
+//! by `poseidon_sponge_free`.
+
+use crate::convert::{be_bytes, repr_from_be_bytes};
+use crate::fields::runtime::BYTES;
+use crate::permutation::{map_messages, Poseidon};
+use crate::precompile::{self, PrecompileError};
+use crate::{pallas, parameters, s128b, sw2, sw3, sw4, sw8, vesta, HashError};
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::ffi::c_char;
+use ff::PrimeField;
+
+pub const POSEIDON_OK: i32 = 0;
+/// A pointer argument is null.
+pub const POSEIDON_ERROR_NULL_POINTER: i32 = 1;
+/// A length is not a multiple of the size of an element, an input is empty or
+/// not a multiple of the rate, or a state is not of rate + capacity elements.
+pub const POSEIDON_ERROR_LENGTH: i32 = 2;
+/// An element is not lower than the modulus.
+pub const POSEIDON_ERROR_NON_CANONICAL: i32 = 3;
+/// The output buffer is shorter than the output.
+pub const POSEIDON_ERROR_OUTPUT_TOO_SMALL: i32 = 4;
+/// The precompile input holds invalid parameters.
+pub const POSEIDON_ERROR_INVALID_PARAMETERS: i32 = 5;
+/// The precompile call exceeds its gas limit.
+pub const POSEIDON_ERROR_OUT_OF_GAS: i32 = 6;
+/// No set of parameters has the given identifier.
+pub const POSEIDON_ERROR_UNKNOWN_PARAMS: i32 = 7;
+
+fn status(result: Result<(), i32>) -> i32 {
+    match result {
+        Ok(()) => POSEIDON_OK,
+        Err(code) => code,
+    }
+}
+
+/// Borrows a C buffer, which must be valid for `len` bytes if not null.
+unsafe fn input_slice<'a>(ptr: *const u8, len: usize) -> Result<&'a [u8], i32> {
+    if ptr.is_null() {
+        return Err(POSEIDON_ERROR_NULL_POINTER);
+    }
+    Ok(core::slice::from_raw_parts(ptr, len))
+}
+
+/// Borrows a mutable C buffer, which must be valid for `len` bytes if not null.
+unsafe fn output_slice<'a>(ptr: *mut u8, len: usize) -> Result<&'a mut [u8], i32> {
+    if ptr.is_null() {
+        return Err(POSEIDON_ERROR_NULL_POINTER);
+    }
+    Ok(core::slice::from_raw_parts_mut(ptr, len))
+}
+
+fn felt_size<GF>() -> usize
+where
+    GF: PrimeField,
+{
+    GF::Repr::default().as_ref().len()
+}
+
+/// Encoding of the elements exchanged with C.
+#[derive(Clone, Copy)]
+enum Encoding {
+    /// The representation of the field, little-endian for the crate's fields.
+    Repr,
+    /// Big-endian integers.
+    BigEndian,
+}
+
+impl Encoding {
+    fn read<GF>(self, bytes: &[u8]) -> Option<GF>
+    where
+        GF: PrimeField,
+    {
+        let repr = match self {
+            Encoding::Repr => {
+                let mut repr = GF::Repr::default();
+                repr.as_mut().copy_from_slice(bytes);
+                repr
+            }
+            Encoding::BigEndian => repr_from_be_bytes::<GF>(bytes),
+        };
+        GF::from_repr(repr).into()
+    }
+
+    fn write<GF>(self, felts: &[GF], bytes: &mut [u8])
+    where
+        GF: PrimeField,
+    {
+        for (chunk, felt) in bytes.chunks_exact_mut(felt_size::<GF>()).zip(felts) {
+            match self {
+                Encoding::Repr => chunk.copy_from_slice(felt.to_repr().as_ref()),
+                Encoding::BigEndian => chunk.copy_from_slice(&be_bytes(felt)),
+            }
+        }
+    }
+}
+
+fn felts_from_bytes<GF>(bytes: &[u8], encoding: Encoding) -> Result<Vec<GF>, i32>
+where
+    GF: PrimeField,
+{
+    let n_bytes = felt_size::<GF>();
+    if !bytes.len().is_multiple_of(n_bytes) {
+        return Err(POSEIDON_ERROR_LENGTH);
+    }
+    bytes
+        .chunks_exact(n_bytes)
+        .map(|chunk| encoding.read(chunk).ok_or(POSEIDON_ERROR_NON_CANONICAL))
+        .collect()
+}
+
+fn hash<GF>(
+    input: &[u8],
+    output: &mut [u8],
+    encoding: Encoding,
+    rate: usize,
+    output_size: usize,
+    hash: fn(&[GF]) -> Vec<GF>,
+) -> Result<(), i32>
+where
+    GF: PrimeField,
+{
+    let inputs = felts_from_bytes::<GF>(input, encoding)?;
+    if inputs.is_empty() || !inputs.len().is_multiple_of(rate) {
+        return Err(POSEIDON_ERROR_LENGTH);
+    }
+    let n_bytes = output_size * felt_size::<GF>();
+    if output.len() < n_bytes {
+        return Err(POSEIDON_ERROR_OUTPUT_TOO_SMALL);
+    }
+    encoding.write(&hash(&inputs), &mut output[..n_bytes]);
+    Ok(())
+}
+
+fn hash_messages<GF>(
+    input: &[u8],
+    message_len: usize,
+    output: &mut [u8],
+    rate: usize,
+    output_size: usize,
+    hash: fn(&[GF]) -> Vec<GF>,
+) -> Result<(), i32>
+where
+    GF: PrimeField,
+{
+    let felt_size = felt_size::<GF>();
+    if message_len == 0
+        || !message_len.is_multiple_of(rate * felt_size)
+        || !input.len().is_multiple_of(message_len)
+    {
+        return Err(POSEIDON_ERROR_LENGTH);
+    }
+    let inputs = felts_from_bytes::<GF>(input, Encoding::Repr)?;
+    let n_bytes = input.len() / message_len * output_size * felt_size;
+    if output.len() < n_bytes {
+        return Err(POSEIDON_ERROR_OUTPUT_TOO_SMALL);
+    }
+    let outputs = map_messages(&inputs, message_len / felt_size, hash);
+    Encoding::Repr.write(&outputs, &mut output[..n_bytes]);
+    Ok(())
+}
+
+fn permute<GF>(state: &mut [u8], permute: fn(&mut [GF]) -> Result<(), HashError>) -> Result<(), i32>
+where
+    GF: PrimeField,
+{
+    let mut felts = felts_from_bytes::<GF>(state, Encoding::Repr)?;
+    permute(&mut felts).map_err(|_| POSEIDON_ERROR_LENGTH)?;
+    Encoding::Repr.write(&felts, state);
+    Ok(())
+}
+
+/// Exports the C-Interface of a set of parameters, see the module
+/// documentation.
+macro_rules! c_interface {
+    (
+        $gf:ty, $params:expr, $hash:path, $permute:path
+        => $c_hash:ident, $c_hash_be:ident, $c_hash_batch:ident,
+        $c_permute:ident, $c_output_size:ident
+    ) => {
+        /// # Safety
+        ///
+        /// Non-null pointers must be valid for their lengths.
+        #[no_mangle]
+        pub unsafe extern "C" fn $c_hash(
+            input: *const u8,
+            input_len: usize,
+            output: *mut u8,
+            output_len: usize,
+        ) -> i32 {
+            status((|| {
+                let input = input_slice(input, input_len)?;
+                let output = output_slice(output, output_len)?;
+                let (rate, output_size) = ($params.rate, $params.output_size);
+                hash::<$gf>(input, output, Encoding::Repr, rate, output_size, $hash)
+            })())
+        }
+
+        /// # Safety
+        ///
+        /// Non-null pointers must be valid for their lengths.
+        #[no_mangle]
+        pub unsafe extern "C" fn $c_hash_be(
+            input: *const u8,
+            input_len: usize,
+            output: *mut u8,
+            output_len: usize,
+        ) -> i32 {
+            status((|| {
+                let input = input_slice(input, input_len)?;
+                let output = output_slice(output, output_len)?;
+                let (rate, output_size) = ($params.rate, $params.output_size);
+                hash::<$gf>(input, output, Encoding::BigEndian, rate, output_size, $hash)
+            })())
+        }
+
+        /// # Safety
+        ///
+        /// Non-null pointers must be valid for their lengths.
+        #[no_mangle]
+        pub unsafe extern "C" fn $c_hash_batch(
+            input: *const u8,
+            input_len: usize,
+            message_len: usize,
+            output: *mut u8,
+            output_len: usize,
+        ) -> i32 {
+            status((|| {
+                let input = input_slice(input, input_len)?;
+                let output = output_slice(output, output_len)?;
+                let (rate, output_size) = ($params.rate, $params.output_size);
+                hash_messages::<$gf>(input, message_len, output, rate, output_size, $hash)
+            })())
+        }
+
+        /// # Safety
+        ///
+        /// A non-null `state` must be valid for `state_len` bytes.
+        #[no_mangle]
+        pub unsafe extern "C" fn $c_permute(state: *mut u8, state_len: usize) -> i32 {
+            status((|| {
+                let state = output_slice(state, state_len)?;
+                permute::<$gf>(state, $permute)
+            })())
+        }
+
+        #[no_mangle]
+        pub extern "C" fn $c_output_size() -> usize {
+            $params.output_size * felt_size::<$gf>()
+        }
+    };
+}
+
+c_interface!(s128b::GF, s128b::PARAMS, crate::hash_s128b, crate::permute_s128b
+    => c_hash_s128b, c_hash_be_s128b, c_hash_batch_s128b,
+    c_permute_s128b, c_output_size_s128b);
+c_interface!(sw2::GF, sw2::PARAMS, crate::hash_sw2, crate::permute_sw2
+    => c_hash_sw2, c_hash_be_sw2, c_hash_batch_sw2,
+    c_permute_sw2, c_output_size_sw2);
+c_interface!(sw3::GF, sw3::PARAMS, crate::hash_sw3, crate::permute_sw3
+    => c_hash_sw3, c_hash_be_sw3, c_hash_batch_sw3,
+    c_permute_sw3, c_output_size_sw3);
+c_interface!(sw4::GF, sw4::PARAMS, crate::hash_sw4, crate::permute_sw4
+    => c_hash_sw4, c_hash_be_sw4, c_hash_batch_sw4,
+    c_permute_sw4, c_output_size_sw4);
+c_interface!(sw8::GF, sw8::PARAMS, crate::hash_sw8, crate::permute_sw8
+    => c_hash_sw8, c_hash_be_sw8, c_hash_batch_sw8,
+    c_permute_sw8, c_output_size_sw8);
+c_interface!(pallas::GF, pallas::PARAMS, crate::hash_pallas, crate::permute_pallas
+    => c_hash_pallas, c_hash_be_pallas, c_hash_batch_pallas,
+    c_permute_pallas, c_output_size_pallas);
+c_interface!(vesta::GF, vesta::PARAMS, crate::hash_vesta, crate::permute_vesta
+    => c_hash_vesta, c_hash_be_vesta, c_hash_batch_vesta,
+    c_permute_vesta, c_output_size_vesta);
+c_interface!(pallas::GF, parameters::poseidon2::pallas::PARAMS,
+    crate::hash_poseidon2_pallas, crate::permute_poseidon2_pallas
+    => c_hash_poseidon2_pallas, c_hash_be_poseidon2_pallas, c_hash_batch_poseidon2_pallas,
+    c_permute_poseidon2_pallas, c_output_size_poseidon2_pallas);
+c_interface!(vesta::GF, parameters::poseidon2::vesta::PARAMS,
+    crate::hash_poseidon2_vesta, crate::permute_poseidon2_vesta
+    => c_hash_poseidon2_vesta, c_hash_be_poseidon2_vesta, c_hash_batch_poseidon2_vesta,
+    c_permute_poseidon2_vesta, c_output_size_poseidon2_vesta);
+c_interface!(sw3::GF, parameters::poseidon2::stark252::PARAMS,
+    crate::hash_poseidon2_stark252, crate::permute_poseidon2_stark252
+    => c_hash_poseidon2_stark252, c_hash_be_poseidon2_stark252,
+    c_hash_batch_poseidon2_stark252, c_permute_poseidon2_stark252,
+    c_output_size_poseidon2_stark252);
+
+// Identifiers of the sets of parameters, indices in `EXPORTED_PARAMS`.
+pub const POSEIDON_PARAMS_S128B: u32 = 0;
+pub const POSEIDON_PARAMS_SW2: u32 = 1;
+pub const POSEIDON_PARAMS_SW3: u32 = 2;
+pub const POSEIDON_PARAMS_SW4: u32 = 3;
+pub const POSEIDON_PARAMS_SW8: u32 = 4;
+pub const POSEIDON_PARAMS_PALLAS: u32 = 5;
+pub const POSEIDON_PARAMS_VESTA: u32 = 6;
+pub const POSEIDON_PARAMS_POSEIDON2_PALLAS: u32 = 7;
+pub const POSEIDON_PARAMS_POSEIDON2_VESTA: u32 = 8;
+pub const POSEIDON_PARAMS_POSEIDON2_STARK252: u32 = 9;
+
+/// Shape of a set of parameters, `poseidon_params_info` in C.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonParamsInfo {
+    /// Name of the set, as in `c_hash_<params>`, NUL-terminated.
+    pub name: *const c_char,
+    pub rate: usize,
+    pub capacity: usize,
+    pub output_size: usize,
+    /// Number of bytes of an element.
+    pub field_size: usize,
+}
+
+struct ExportedParams {
+    name: &'static [u8],
+    rate: usize,
+    capacity: usize,
+    output_size: usize,
+    field_size: usize,
+    permute: fn(&mut [u8]) -> Result<(), i32>,
+}
+
+/// Describes a set of parameters and its permutation over bytes.
+macro_rules! exported_params {
+    ($name:literal, $gf:ty, $params:expr, $permute:path) => {
+        ExportedParams {
+            name: concat!($name, "\0").as_bytes(),
+            rate: $params.rate,
+            capacity: $params.capacity,
+            output_size: $params.output_size,
+            field_size: core::mem::size_of::<<$gf as PrimeField>::Repr>(),
+            permute: |state| permute::<$gf>(state, $permute),
+        }
+    };
+}
+
+static EXPORTED_PARAMS: [ExportedParams; 10] = [
+    exported_params!("s128b", s128b::GF, s128b::PARAMS, crate::permute_s128b),
+    exported_params!("sw2", sw2::GF, sw2::PARAMS, crate::permute_sw2),
+    exported_params!("sw3", sw3::GF, sw3::PARAMS, crate::permute_sw3),
+    exported_params!("sw4", sw4::GF, sw4::PARAMS, crate::permute_sw4),
+    exported_params!("sw8", sw8::GF, sw8::PARAMS, crate::permute_sw8),
+    exported_params!("pallas", pallas::GF, pallas::PARAMS, crate::permute_pallas),
+    exported_params!("vesta", vesta::GF, vesta::PARAMS, crate::permute_vesta),
+    exported_params!(
+        "poseidon2_pallas",
+        pallas::GF,
+        parameters::poseidon2::pallas::PARAMS,
+        crate::permute_poseidon2_pallas
+    ),
+    exported_params!(
+        "poseidon2_vesta",
+        vesta::GF,
+        parameters::poseidon2::vesta::PARAMS,
+        crate::permute_poseidon2_vesta
+    ),
+    exported_params!(
+        "poseidon2_stark252",
+        sw3::GF,
+        parameters::poseidon2::stark252::PARAMS,
+        crate::permute_poseidon2_stark252
+    ),
+];
+
+fn exported_params(param_id: u32) -> Result<&'static ExportedParams, i32> {
+    EXPORTED_PARAMS
+        .get(param_id as usize)
+        .ok_or(POSEIDON_ERROR_UNKNOWN_PARAMS)
+}
+
+/// Returns the number of sets of parameters, whose identifiers are lower.
+#[no_mangle]
+pub extern "C" fn poseidon_params_count() -> u32 {
+    EXPORTED_PARAMS.len() as u32
+}
+
+/// Writes the shape of a set of parameters to `info`.
+///
+/// # Safety
+///
+/// A non-null `info` must be valid for writes.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_get_params_info(
+    param_id: u32,
+    info: *mut PoseidonParamsInfo,
+) -> i32 {
+    status((|| {
+        let params = exported_params(param_id)?;
+        let info = info.as_mut().ok_or(POSEIDON_ERROR_NULL_POINTER)?;
+        *info = PoseidonParamsInfo {
+            name: params.name.as_ptr() as *const c_char,
+            rate: params.rate,
+            capacity: params.capacity,
+            output_size: params.output_size,
+            field_size: params.field_size,
+        };
+        Ok(())
+    })())
+}
+
+/// Permutes in place a state of rate + capacity elements of a set of
+/// parameters, as `c_permute_<params>`.
+///
+/// # Safety
+///
+/// A non-null `state` must be valid for `state_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_permute(param_id: u32, state: *mut u8, state_len: usize) -> i32 {
+    status((|| {
+        let params = exported_params(param_id)?;
+        (params.permute)(output_slice(state, state_len)?)
+    })())
+}
+
+/// Returns the version of the crate, NUL-terminated.
+#[no_mangle]
+pub extern "C" fn poseidon_version() -> *const c_char {
+    concat!(env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Sponge of a set of parameters, opaque to C as `poseidon_sponge`.
+///
+/// Absorbing a message whose length is a multiple of the rate then squeezing
+/// the output size gives the same hash value as `c_hash_<params>`.
+#[derive(Clone)]
+pub enum PoseidonSponge {
+    S128b(Poseidon<'static, s128b::GF>),
+    Sw2(Poseidon<'static, sw2::GF>),
+    Sw3(Poseidon<'static, sw3::GF>),
+    Sw4(Poseidon<'static, sw4::GF>),
+    Sw8(Poseidon<'static, sw8::GF>),
+    Pallas(Poseidon<'static, pallas::GF>),
+    Vesta(Poseidon<'static, vesta::GF>),
+}
+
+/// Evaluates `$body` with `$poseidon` bound to the sponge of any variant.
+macro_rules! with_poseidon {
+    ($sponge:expr, $poseidon:ident => $body:expr) => {
+        match $sponge {
+            PoseidonSponge::S128b($poseidon) => $body,
+            PoseidonSponge::Sw2($poseidon) => $body,
+            PoseidonSponge::Sw3($poseidon) => $body,
+            PoseidonSponge::Sw4($poseidon) => $body,
+            PoseidonSponge::Sw8($poseidon) => $body,
+            PoseidonSponge::Pallas($poseidon) => $body,
+            PoseidonSponge::Vesta($poseidon) => $body,
+        }
+    };
+}
+
+impl PoseidonSponge {
+    /// Creates a sponge for one of the `POSEIDON_PARAMS_*` sets, but the
+    /// Poseidon2 ones.
+    pub fn new(param_id: u32) -> Option<Self> {
+        Some(match param_id {
+            POSEIDON_PARAMS_S128B => Self::S128b(Poseidon::new(s128b::prepared())),
+            POSEIDON_PARAMS_SW2 => Self::Sw2(Poseidon::new(sw2::prepared())),
+            POSEIDON_PARAMS_SW3 => Self::Sw3(Poseidon::new(sw3::prepared())),
+            POSEIDON_PARAMS_SW4 => Self::Sw4(Poseidon::new(sw4::prepared())),
+            POSEIDON_PARAMS_SW8 => Self::Sw8(Poseidon::new(sw8::prepared())),
+            POSEIDON_PARAMS_PALLAS => Self::Pallas(Poseidon::new(pallas::prepared())),
+            POSEIDON_PARAMS_VESTA => Self::Vesta(Poseidon::new(vesta::prepared())),
+            _ => return None,
+        })
+    }
+
+    /// Absorbs the elements of `input`, or none of them if one is invalid.
+    fn absorb(&mut self, input: &[u8]) -> Result<(), i32> {
+        with_poseidon!(self, poseidon => absorb(poseidon, input))
+    }
+
+    /// Fills `output` with squeezed elements.
+    fn squeeze(&mut self, output: &mut [u8]) -> Result<(), i32> {
+        with_poseidon!(self, poseidon => squeeze(poseidon, output))
+    }
+}
+
+fn absorb<GF>(poseidon: &mut Poseidon<GF>, input: &[u8]) -> Result<(), i32>
+where
+    GF: PrimeField,
+{
+    for felt in felts_from_bytes::<GF>(input, Encoding::Repr)? {
+        poseidon.absorb(&felt);
+    }
+    Ok(())
+}
+
+fn squeeze<GF>(poseidon: &mut Poseidon<GF>, output: &mut [u8]) -> Result<(), i32>
+where
+    GF: PrimeField,
+{
+    let n_bytes = felt_size::<GF>();
+    if !output.len().is_multiple_of(n_bytes) {
+        return Err(POSEIDON_ERROR_LENGTH);
+    }
+    for chunk in output.chunks_exact_mut(n_bytes) {
+        chunk.copy_from_slice(poseidon.squeeze().to_repr().as_ref());
+    }
+    Ok(())
+}
+
+/// Creates a sponge for one of the `POSEIDON_PARAMS_*` sets, or returns null
+/// for an unknown or Poseidon2 set.
+#[no_mangle]
+pub extern "C" fn poseidon_sponge_new(param_id: u32) -> *mut PoseidonSponge {
+    match PoseidonSponge::new(param_id) {
+        Some(sponge) => Box::into_raw(Box::new(sponge)),
+        None => core::ptr::null_mut(),
+    }
+}
+
+/// Creates a copy of a sponge, or returns null if `sponge` is null.
+///
+/// # Safety
+///
+/// A non-null `sponge` must have been returned by `poseidon_sponge_new` or
+/// `poseidon_sponge_clone` and not freed.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_sponge_clone(
+    sponge: *const PoseidonSponge,
+) -> *mut PoseidonSponge {
+    match sponge.as_ref() {
+        Some(sponge) => Box::into_raw(Box::new(sponge.clone())),
+        None => core::ptr::null_mut(),
+    }
+}
+
+/// Absorbs `input`, which must hold a whole number of elements.
+///
+/// # Safety
+///
+/// A non-null `sponge` must be a live handle, see [`poseidon_sponge_clone`],
+/// and a non-null `input` must be valid for `input_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_sponge_absorb(
+    sponge: *mut PoseidonSponge,
+    input: *const u8,
+    input_len: usize,
+) -> i32 {
+    status((|| {
+        let sponge = sponge.as_mut().ok_or(POSEIDON_ERROR_NULL_POINTER)?;
+        sponge.absorb(input_slice(input, input_len)?)
+    })())
+}
+
+/// Squeezes `output_len` bytes, which must be a whole number of elements.
+///
+/// # Safety
+///
+/// A non-null `sponge` must be a live handle, see [`poseidon_sponge_clone`],
+/// and a non-null `output` must be valid for `output_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_sponge_squeeze(
+    sponge: *mut PoseidonSponge,
+    output: *mut u8,
+    output_len: usize,
+) -> i32 {
+    status((|| {
+        let sponge = sponge.as_mut().ok_or(POSEIDON_ERROR_NULL_POINTER)?;
+        sponge.squeeze(output_slice(output, output_len)?)
+    })())
+}
+
+/// Releases a sponge. Does nothing if `sponge` is null.
+///
+/// # Safety
+///
+/// A non-null `sponge` must be a live handle, see [`poseidon_sponge_clone`],
+/// and must not be used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn poseidon_sponge_free(sponge: *mut PoseidonSponge) {
+    if !sponge.is_null() {
+        drop(Box::from_raw(sponge));
+    }
+}
+
+fn precompile_status(error: PrecompileError) -> i32 {
+    match error {
+        PrecompileError::UnalignedInput { .. }
+        | PrecompileError::ShortInput { .. }
+        | PrecompileError::LongInput { .. }
+        | PrecompileError::Hash(_) => POSEIDON_ERROR_LENGTH,
+        PrecompileError::NonCanonical { .. } => POSEIDON_ERROR_NON_CANONICAL,
+        PrecompileError::ValueTooLarge { .. }
+        | PrecompileError::InvalidModulus
+        | PrecompileError::Parameters(_) => POSEIDON_ERROR_INVALID_PARAMETERS,
+        PrecompileError::OutOfGas { .. } => POSEIDON_ERROR_OUT_OF_GAS,
+    }
+}
+
+/// Writes the gas cost of an EIP-5988 precompile call to `gas`, see
+/// [`precompile::required_gas`].
+///
+/// # Safety
+///
+/// Non-null pointers must be valid for their lengths.
+#[no_mangle]
+pub unsafe extern "C" fn c_precompile_required_gas(
+    input: *const u8,
+    input_len: usize,
+    gas: *mut u64,
+) -> i32 {
+    status((|| {
+        let input = input_slice(input, input_len)?;
+        if gas.is_null() {
+            return Err(POSEIDON_ERROR_NULL_POINTER);
+        }
+        *gas = precompile::required_gas(input).map_err(precompile_status)?;
+        Ok(())
+    })())
+}
+
+/// Runs an EIP-5988 precompile call, see [`precompile::run`].
+///
+/// Writes the hash value to `output`, its length to `output_written` and the
+/// gas used to `gas_used`.
+///
+/// # Safety
+///
+/// Non-null pointers must be valid for their lengths.
+#[no_mangle]
+pub unsafe extern "C" fn c_precompile_run(
+    input: *const u8,
+    input_len: usize,
+    gas_limit: u64,
+    output: *mut u8,
+    output_len: usize,
+    output_written: *mut usize,
+    gas_used: *mut u64,
+) -> i32 {
+    status((|| {
+        let input = input_slice(input, input_len)?;
+        let output = output_slice(output, output_len)?;
+        if output_written.is_null() || gas_used.is_null() {
+            return Err(POSEIDON_ERROR_NULL_POINTER);
+        }
+        let (result, gas) = precompile::run(input, gas_limit).map_err(precompile_status)?;
+        if output.len() < result.len() {
+            return Err(POSEIDON_ERROR_OUTPUT_TOO_SMALL);
+        }
+        output[..result.len()].copy_from_slice(&result);
+        *output_written = result.len();
+        *gas_used = gas;
+        Ok(())
+    })())
+}
+
+fn hash_with_parameters(parameters: &[u8], input: &[u8], output: &mut [u8]) -> Result<usize, i32> {
+    let params = precompile::decode_parameters(parameters).map_err(precompile_status)?;
+    let field = params.field();
+    if !input.len().is_multiple_of(BYTES) {
+        return Err(POSEIDON_ERROR_LENGTH);
+    }
+    let inputs = input
+        .chunks_exact(BYTES)
+        .map(|chunk| {
+            field
+                .element_from_be_bytes(chunk.try_into().unwrap())
+                .ok_or(POSEIDON_ERROR_NON_CANONICAL)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let result = params.hash(&inputs).map_err(|_| POSEIDON_ERROR_LENGTH)?;
+    let n_bytes = result.len() * BYTES;
+    if output.len() < n_bytes {
+        return Err(POSEIDON_ERROR_OUTPUT_TOO_SMALL);
+    }
+    for (chunk, x) in output.chunks_exact_mut(BYTES).zip(&result) {
+        chunk.copy_from_slice(&field.element_to_be_bytes(x));
+    }
+    Ok(n_bytes)
+}
+
+/// Hashes `input` with parameters supplied by the caller.
+///
+/// `parameters` are laid out as the input of the EIP-5988 precompile without
+/// its message, see [`precompile::decode_parameters`], and are fully
+/// validated. `input` and `output` are made of 32-byte big-endian words, like
+/// `parameters`. Writes the length of the hash value to `output_written`.
+///
+/// # Safety
+///
+/// Non-null pointers must be valid for their lengths.
+#[no_mangle]
+pub unsafe extern "C" fn c_hash_with_parameters(
+    parameters: *const u8,
+    parameters_len: usize,
+    input: *const u8,
+    input_len: usize,
+    output: *mut u8,
+    output_len: usize,
+    output_written: *mut usize,
+) -> i32 {
+    status((|| {
+        let parameters = input_slice(parameters, parameters_len)?;
+        let input = input_slice(input, input_len)?;
+        let output = output_slice(output, output_len)?;
+        if output_written.is_null() {
+            return Err(POSEIDON_ERROR_NULL_POINTER);
+        }
+        *output_written = hash_with_parameters(parameters, input, output)?;
+        Ok(())
+    })())
+}
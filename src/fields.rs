@@ -7,7 +7,9 @@ use core::default::Default;
 // use core::ops::{Add, Mul};
 
 pub mod arithmetic;
+pub mod montgomery;
 pub mod prime;
+pub mod runtime;
 
 pub trait Zero: Sized {
     fn zero() -> Self;
@@ -13,19 +13,19 @@ pub const MAX: u64 = u64::MAX;
 
 /// Get double digit from 2 digits, low and high.
 #[inline(always)]
-pub fn as_dbl_digit(lo: u64, hi: u64) -> u128 {
+pub const fn as_dbl_digit(lo: u64, hi: u64) -> u128 {
     ((hi as u128) << BITS) + lo as u128
 }
 
 /// Get low and high digits respectively from a double digit.
 #[inline(always)]
-pub fn from_dbl_digit(val: u128) -> (u64, u64) {
+pub const fn from_dbl_digit(val: u128) -> (u64, u64) {
     (val as u64, (val >> BITS) as u64)
 }
 
 /// Computes `a - (b + borrow)`, returning the result and the new borrow.
 #[inline(always)]
-pub fn sbb(a: &mut u64, b: u64, borrow: u8) -> u8 {
+pub const fn sbb(a: &mut u64, b: u64, borrow: u8) -> u8 {
     let ret = (1u128 << BITS) + (*a as u128) - (b as u128) - (borrow as u128);
     *a = ret as u64;
     (ret >> BITS == 0) as u8
@@ -33,7 +33,7 @@ pub fn sbb(a: &mut u64, b: u64, borrow: u8) -> u8 {
 
 /// Computes `a += b + carry`, returns the new carry.
 #[inline(always)]
-pub fn adc(a: &mut u64, b: u64, carry: u64) -> u64 {
+pub const fn adc(a: &mut u64, b: u64, carry: u64) -> u64 {
     let tmp = (*a as u128) + (b as u128) + (carry as u128);
     *a = tmp as u64;
     (tmp >> BITS) as u64
@@ -41,7 +41,7 @@ pub fn adc(a: &mut u64, b: u64, carry: u64) -> u64 {
 
 /// Computes `a += (b * c) + carry`, returning the new carry over.
 #[inline(always)]
-pub fn mac(a: &mut u64, b: u64, c: u64, carry: u64) -> u64 {
+pub const fn mac(a: &mut u64, b: u64, c: u64, carry: u64) -> u64 {
     let tmp = (*a as u128) + ((b as u128) * (c as u128)) + (carry as u128);
     *a = tmp as u64;
     (tmp >> BITS) as u64
@@ -0,0 +1,221 @@
+//! Compile-time conversion of decimal constants to Montgomery form.
+//!
+//! Prime fields derived with `ff` store an element a as a·R mod p, with
+//! R = 2^(64·L) for L limbs. The functions below are `const`, which allows
+//! building static tables of field elements from the decimal strings of the
+//! parameters, without parsing them at runtime. Limbs are little-endian.
+
+use super::arithmetic::{adc, mac, sbb};
+
+/// Returns whether a < b.
+pub const fn lt<const L: usize>(a: &[u64; L], b: &[u64; L]) -> bool {
+    let mut borrow = 0u8;
+    let mut i = 0;
+    while i < L {
+        let mut x = a[i];
+        borrow = sbb(&mut x, b[i], borrow);
+        i += 1;
+    }
+    borrow == 1
+}
+
+/// Parses a decimal string into an integer.
+///
+/// Panics, at compile time in const contexts, on invalid digits or integers
+/// exceeding the limbs.
+pub const fn limbs_from_decimal<const L: usize>(s: &str) -> [u64; L] {
+    let bytes = s.as_bytes();
+    assert!(!bytes.is_empty(), "Empty decimal string");
+    let mut result = [0u64; L];
+    let mut i = 0;
+    while i < bytes.len() {
+        let digit = bytes[i];
+        assert!(digit.is_ascii_digit(), "Invalid decimal digit");
+        // result = result * 10 + digit
+        let mut carry = (digit - b'0') as u64;
+        let mut j = 0;
+        while j < L {
+            let mut acc = 0u64;
+            carry = mac(&mut acc, result[j], 10, carry);
+            result[j] = acc;
+            j += 1;
+        }
+        assert!(carry == 0, "Decimal string exceeds the limbs");
+        i += 1;
+    }
+    result
+}
+
+/// Parses a decimal string into an integer lower than the modulus.
+///
+/// Panics, at compile time in const contexts, on invalid digits or integers
+/// not lower than the modulus.
+pub const fn from_decimal<const L: usize>(s: &str, modulus: &[u64; L]) -> [u64; L] {
+    let result = limbs_from_decimal(s);
+    assert!(lt(&result, modulus), "Decimal string exceeds the modulus");
+    result
+}
+
+/// Returns -p^-1 mod 2^64 for an odd p, from its least significant limb.
+pub const fn mont_inv(p0: u64) -> u64 {
+    // Newton iteration, each step doubling the number of correct bits.
+    let mut inv = 1u64;
+    let mut i = 0;
+    while i < 6 {
+        inv = inv.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inv)));
+        i += 1;
+    }
+    inv.wrapping_neg()
+}
+
+/// Returns R^2 mod p, by doubling 1 modulo p 2·64·L times.
+pub const fn mont_r2<const L: usize>(modulus: &[u64; L]) -> [u64; L] {
+    let mut r2 = [0u64; L];
+    r2[0] = 1;
+    let mut i = 0;
+    while i < 2 * 64 * L {
+        // r2 = 2·r2 mod p, r2 being lower than p.
+        let mut carry = 0u64;
+        let mut j = 0;
+        while j < L {
+            let x = r2[j];
+            carry = adc(&mut r2[j], x, carry);
+            j += 1;
+        }
+        if carry != 0 || !lt(&r2, modulus) {
+            let mut borrow = 0u8;
+            let mut j = 0;
+            while j < L {
+                borrow = sbb(&mut r2[j], modulus[j], borrow);
+                j += 1;
+            }
+        }
+        i += 1;
+    }
+    r2
+}
+
+/// Computes a·b·R^-1 mod p, with inv = -p^-1 mod 2^64 (CIOS method).
+pub const fn mont_mul<const L: usize>(
+    a: &[u64; L],
+    b: &[u64; L],
+    modulus: &[u64; L],
+    inv: u64,
+) -> [u64; L] {
+    let mut t = [0u64; L];
+    let mut t_hi = 0u64;
+    let mut i = 0;
+    while i < L {
+        // t += a * b[i]
+        let mut carry = 0u64;
+        let mut j = 0;
+        while j < L {
+            carry = mac(&mut t[j], a[j], b[i], carry);
+            j += 1;
+        }
+        let t_top = adc(&mut t_hi, carry, 0);
+
+        // t = (t + m * p) / 2^64, with m chosen so that the division is exact.
+        let m = t[0].wrapping_mul(inv);
+        let mut low = t[0];
+        let mut carry = mac(&mut low, m, modulus[0], 0);
+        let mut j = 1;
+        while j < L {
+            let mut x = t[j];
+            carry = mac(&mut x, m, modulus[j], carry);
+            t[j - 1] = x;
+            j += 1;
+        }
+        let mut x = t_hi;
+        let top = adc(&mut x, carry, 0);
+        t[L - 1] = x;
+        t_hi = t_top + top;
+        i += 1;
+    }
+
+    // The result is lower than 2p, a single subtraction reduces it.
+    if t_hi != 0 || !lt(&t, modulus) {
+        let mut borrow = 0u8;
+        let mut j = 0;
+        while j < L {
+            borrow = sbb(&mut t[j], modulus[j], borrow);
+            j += 1;
+        }
+    }
+    t
+}
+
+/// Parses a decimal string into Montgomery form, r2 being R^2 mod p.
+pub const fn mont_from_decimal<const L: usize>(
+    s: &str,
+    modulus: &[u64; L],
+    r2: &[u64; L],
+    inv: u64,
+) -> [u64; L] {
+    mont_mul(&from_decimal(s, modulus), r2, modulus, inv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // p = 2^64 + 13, R = 2^128, limbs are little-endian.
+    const P: [u64; 2] = [13, 1];
+
+    fn inv(p0: u64) -> u64 {
+        let mut inv = 1u64;
+        for _ in 0..63 {
+            inv = inv.wrapping_mul(inv);
+            inv = inv.wrapping_mul(p0);
+        }
+        inv.wrapping_neg()
+    }
+
+    #[test]
+    fn test_lt() {
+        assert!(lt(&[12, 1], &P));
+        assert!(lt(&[u64::MAX, 0], &P));
+        assert!(!lt(&P, &P));
+        assert!(!lt(&[0, 2], &P));
+    }
+
+    #[test]
+    fn test_from_decimal() {
+        assert_eq!(from_decimal("0", &P), [0, 0]);
+        assert_eq!(from_decimal("18446744073709551617", &P), [1, 1]);
+        assert_eq!(from_decimal("18446744073709551628", &P), [12, 1]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_decimal_exceeds_modulus() {
+        from_decimal("18446744073709551629", &P);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_from_decimal_invalid_digit() {
+        from_decimal("12a", &P);
+    }
+
+    #[test]
+    fn test_mont_mul() {
+        let inv = inv(P[0]);
+        let p = (1u128 << 64) + 13;
+        // R mod p = 2^128 mod p = 169, and R^2 mod p = 169^2.
+        let r2 = [169 * 169, 0];
+        let a = from_decimal("18446744073709551600", &P);
+        let mont_a = mont_mul(&a, &r2, &P, inv);
+        let expected = (((1u128 << 64) - 16) % p) * 169 % p;
+        assert_eq!(mont_a, [expected as u64, (expected >> 64) as u64]);
+        // Multiplying by 1 leaves Montgomery form.
+        assert_eq!(mont_mul(&mont_a, &[1, 0], &P, inv), a);
+    }
+
+    #[test]
+    fn test_mont_constants() {
+        assert_eq!(mont_inv(P[0]), inv(P[0]));
+        assert_eq!(mont_r2(&P), [169 * 169, 0]);
+        assert_eq!(limbs_from_decimal::<2>("18446744073709551629"), P);
+    }
+}
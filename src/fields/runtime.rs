@@ -0,0 +1,233 @@
+//! Prime fields whose modulus is only known at runtime.
+//!
+//! Moduli are odd integers of at most 256 bits. Elements are kept in Montgomery
+//! form, as with fields derived with `ff`, and converted from and to 32-byte
+//! big-endian integers. Primality of the modulus is not checked.
+
+use super::arithmetic::{adc, sbb};
+use super::montgomery::{lt, mont_inv, mont_mul, mont_r2};
+
+pub const LIMBS: usize = 4;
+
+/// Number of bytes of an encoded element.
+pub const BYTES: usize = LIMBS * 8;
+
+/// An element of a [`RuntimeField`], meaningful only with its field.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RuntimeElement([u64; LIMBS]);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuntimeField {
+    modulus: [u64; LIMBS],
+    inv: u64,
+    r2: [u64; LIMBS],
+}
+
+impl RuntimeField {
+    /// Builds the field of integers modulo a big-endian `modulus`, which must
+    /// be odd and greater than 1.
+    pub fn from_be_bytes(modulus: &[u8; BYTES]) -> Option<Self> {
+        let modulus = limbs_from_be_bytes(modulus);
+        if modulus[0] & 1 == 0 || modulus == [1, 0, 0, 0] {
+            return None;
+        }
+        Some(RuntimeField {
+            modulus,
+            inv: mont_inv(modulus[0]),
+            r2: mont_r2(&modulus),
+        })
+    }
+
+    /// Returns the limbs of the modulus, least significant first.
+    pub fn modulus(&self) -> &[u64; LIMBS] {
+        &self.modulus
+    }
+
+    pub fn zero(&self) -> RuntimeElement {
+        RuntimeElement::default()
+    }
+
+    pub fn one(&self) -> RuntimeElement {
+        RuntimeElement(mont_mul(&[1, 0, 0, 0], &self.r2, &self.modulus, self.inv))
+    }
+
+    /// Reads a big-endian integer, which must be lower than the modulus.
+    pub fn element_from_be_bytes(&self, bytes: &[u8; BYTES]) -> Option<RuntimeElement> {
+        let x = limbs_from_be_bytes(bytes);
+        if !lt(&x, &self.modulus) {
+            return None;
+        }
+        Some(RuntimeElement(mont_mul(
+            &x,
+            &self.r2,
+            &self.modulus,
+            self.inv,
+        )))
+    }
+
+    pub fn element_to_be_bytes(&self, x: &RuntimeElement) -> [u8; BYTES] {
+        let x = mont_mul(&x.0, &[1, 0, 0, 0], &self.modulus, self.inv);
+        let mut bytes = [0u8; BYTES];
+        for (chunk, limb) in bytes.chunks_exact_mut(8).zip(x.iter().rev()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        bytes
+    }
+
+    pub fn add(&self, a: &RuntimeElement, b: &RuntimeElement) -> RuntimeElement {
+        let mut sum = a.0;
+        let mut carry = 0u64;
+        for (x, y) in sum.iter_mut().zip(b.0) {
+            carry = adc(x, y, carry);
+        }
+        if carry != 0 || !lt(&sum, &self.modulus) {
+            sub_assign(&mut sum, &self.modulus);
+        }
+        RuntimeElement(sum)
+    }
+
+    pub fn sub(&self, a: &RuntimeElement, b: &RuntimeElement) -> RuntimeElement {
+        let mut difference = a.0;
+        if sub_assign(&mut difference, &b.0) != 0 {
+            add_assign(&mut difference, &self.modulus);
+        }
+        RuntimeElement(difference)
+    }
+
+    pub fn mul(&self, a: &RuntimeElement, b: &RuntimeElement) -> RuntimeElement {
+        RuntimeElement(mont_mul(&a.0, &b.0, &self.modulus, self.inv))
+    }
+
+    /// Computes x^power by square-and-multiply, with ⌊log2 power⌋ squarings
+    /// and one multiplication per set bit but the highest.
+    pub fn pow(&self, x: &RuntimeElement, power: u64) -> RuntimeElement {
+        if power == 0 {
+            return self.one();
+        }
+        let mut result = *x;
+        for i in (0..u64::BITS - 1 - power.leading_zeros()).rev() {
+            result = self.mul(&result, &result);
+            if (power >> i) & 1 == 1 {
+                result = self.mul(&result, x);
+            }
+        }
+        result
+    }
+
+    /// Computes x^-1 as x^(p - 2), by Fermat's little theorem.
+    ///
+    /// Returns None if x is zero or, p not being checked to be prime, if the
+    /// result is not the inverse of x.
+    pub fn invert(&self, x: &RuntimeElement) -> Option<RuntimeElement> {
+        let mut exponent = self.modulus;
+        sub_assign(&mut exponent, &[2, 0, 0, 0]);
+        let mut result = self.one();
+        for limb in exponent.iter().rev() {
+            for i in (0..u64::BITS).rev() {
+                result = self.mul(&result, &result);
+                if (limb >> i) & 1 == 1 {
+                    result = self.mul(&result, x);
+                }
+            }
+        }
+        (self.mul(&result, x) == self.one()).then_some(result)
+    }
+}
+
+fn limbs_from_be_bytes(bytes: &[u8; BYTES]) -> [u64; LIMBS] {
+    let mut limbs = [0u64; LIMBS];
+    for (limb, chunk) in limbs.iter_mut().rev().zip(bytes.chunks_exact(8)) {
+        *limb = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+    limbs
+}
+
+/// Computes a - b mod 2^256, returning the borrow.
+fn sub_assign(a: &mut [u64; LIMBS], b: &[u64; LIMBS]) -> u8 {
+    let mut borrow = 0u8;
+    for (x, y) in a.iter_mut().zip(b) {
+        borrow = sbb(x, *y, borrow);
+    }
+    borrow
+}
+
+/// Computes a + b mod 2^256.
+fn add_assign(a: &mut [u64; LIMBS], b: &[u64; LIMBS]) {
+    let mut carry = 0u64;
+    for (x, y) in a.iter_mut().zip(b) {
+        carry = adc(x, *y, carry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn be_bytes(x: u128) -> [u8; BYTES] {
+        let mut bytes = [0u8; BYTES];
+        bytes[16..].copy_from_slice(&x.to_be_bytes());
+        bytes
+    }
+
+    // p = 2^64 + 13.
+    const P: u128 = (1 << 64) + 13;
+
+    #[test]
+    fn test_new() {
+        assert!(RuntimeField::from_be_bytes(&be_bytes(P)).is_some());
+        assert!(RuntimeField::from_be_bytes(&be_bytes(P + 1)).is_none());
+        assert!(RuntimeField::from_be_bytes(&be_bytes(1)).is_none());
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let field = RuntimeField::from_be_bytes(&be_bytes(P)).unwrap();
+        let a = field.element_from_be_bytes(&be_bytes(P - 2)).unwrap();
+        let b = field.element_from_be_bytes(&be_bytes(5)).unwrap();
+        assert_eq!(field.element_to_be_bytes(&a), be_bytes(P - 2));
+        assert_eq!(field.element_to_be_bytes(&field.add(&a, &b)), be_bytes(3));
+        // (p - 2)·5 = -10 mod p.
+        assert_eq!(
+            field.element_to_be_bytes(&field.mul(&a, &b)),
+            be_bytes(P - 10)
+        );
+        // (-2)^5 = -32 mod p.
+        assert_eq!(
+            field.element_to_be_bytes(&field.pow(&a, 5)),
+            be_bytes(P - 32)
+        );
+        assert_eq!(field.pow(&a, 0), field.one());
+        assert_eq!(field.element_to_be_bytes(&field.sub(&b, &a)), be_bytes(7));
+        assert_eq!(field.sub(&a, &a), field.zero());
+        let inverse = field.invert(&a).unwrap();
+        assert_eq!(field.mul(&a, &inverse), field.one());
+        assert!(field.invert(&field.zero()).is_none());
+        assert_eq!(field.add(&field.zero(), &b), b);
+        assert!(field.element_from_be_bytes(&be_bytes(P)).is_none());
+    }
+
+    #[test]
+    fn test_invert_composite() {
+        // 2^13 = 2 mod 15, so x^(p - 2) is not the inverse of 2.
+        let field = RuntimeField::from_be_bytes(&be_bytes(15)).unwrap();
+        let two = field.element_from_be_bytes(&be_bytes(2)).unwrap();
+        assert!(field.invert(&two).is_none());
+    }
+
+    #[test]
+    fn test_full_width_modulus() {
+        // p = 2^256 - 189, the largest 256-bit prime.
+        let mut modulus = [0xff; BYTES];
+        modulus[BYTES - 1] = 0x43;
+        let field = RuntimeField::from_be_bytes(&modulus).unwrap();
+        let mut minus_one = modulus;
+        minus_one[BYTES - 1] -= 1;
+        let x = field.element_from_be_bytes(&minus_one).unwrap();
+        assert_eq!(field.mul(&x, &x), field.one());
+        assert_eq!(field.element_to_be_bytes(&field.add(&x, &x)), {
+            let mut minus_two = modulus;
+            minus_two[BYTES - 1] -= 2;
+            minus_two
+        });
+    }
+}
@@ -0,0 +1,526 @@
+//! Fixed-width Poseidon permutation over static constant tables.
+//!
+//! [`PoseidonPermutation`] keeps its state in a `[GF; T]` array and reads its
+//! MDS matrix and round constants from `'static` slices, so that permuting and
+//! hashing never allocate. Each set of parameters of the crate provides its
+//! tables, converted at compile time, and its permutation as `PERMUTATION`,
+//! [`Poseidon2Permutation`] for the Poseidon2 sets.
+//!
+//! ```
+//! use poseidon::parameters::s128b::{GF, PERMUTATION};
+//! let mut output = [GF::from(0)];
+//! PERMUTATION.hash(&[GF::from(7), GF::from(98)], &mut output).unwrap();
+//! ```
+//!
+//! This module and the tables are all that remains without the `alloc`
+//! feature. Each set then hashes into an array of its output size:
+//!
+//! ```
+//! use poseidon::parameters::poseidon2::pallas::{hash, GF};
+//! let output: [GF; 1] = hash(&[GF::from(7), GF::from(98)]).unwrap();
+//! ```
+
+use crate::parameters::{Parameters, Poseidon2Parameters};
+use core::fmt;
+use ff::PrimeField;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashError {
+    EmptyInputs,
+    /// The input length is not a multiple of the rate.
+    InputLength {
+        length: usize,
+        rate: usize,
+    },
+    /// The output length differs from the output size of the parameters.
+    OutputLength {
+        length: usize,
+        output_size: usize,
+    },
+    /// The state length differs from the width of the permutation.
+    StateLength {
+        length: usize,
+        width: usize,
+    },
+}
+
+impl fmt::Display for HashError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HashError::EmptyInputs => write!(f, "Empty inputs"),
+            HashError::InputLength { length, rate } => write!(
+                f,
+                "Input length {} must be a multiple of the hash rate {}",
+                length, rate
+            ),
+            HashError::OutputLength {
+                length,
+                output_size,
+            } => write!(
+                f,
+                "Output length {} must be equal to the output size {}",
+                length, output_size
+            ),
+            HashError::StateLength { length, width } => write!(
+                f,
+                "State length {} must be equal to the width {}",
+                length, width
+            ),
+        }
+    }
+}
+
+pub(crate) fn ark<GF>(state: &mut [GF], round_constants: &[GF], round: usize)
+where
+    GF: PrimeField,
+{
+    let size = state.len();
+    for i in 0..size {
+        state[i].add_assign(&round_constants[round * size + i]);
+    }
+}
+
+pub(crate) fn sbox_full<GF>(state: &mut [GF], power: u8)
+where
+    GF: PrimeField,
+{
+    for j in 0..state.len() {
+        let aux = state[j];
+        for _ in 1..power {
+            state[j].mul_assign(aux);
+        }
+    }
+}
+
+pub(crate) fn sbox_partial<GF>(state: &mut [GF], power: u8)
+where
+    GF: PrimeField,
+{
+    let i = state.len() - 1;
+    let aux = state[i];
+    for _ in 1..power {
+        state[i].mul_assign(&aux);
+    }
+}
+
+pub(crate) fn sbox_first<GF>(state: &mut [GF], power: u8)
+where
+    GF: PrimeField,
+{
+    let aux = state[0];
+    for _ in 1..power {
+        state[0].mul_assign(&aux);
+    }
+}
+
+pub(crate) fn mix_internal<GF>(state: &mut [GF], internal_diagonal: &[GF])
+where
+    GF: PrimeField,
+{
+    let sum = state.iter().fold(GF::ZERO, |sum, x| sum + x);
+    for (x, d) in state.iter_mut().zip(internal_diagonal) {
+        x.mul_assign(d);
+        x.add_assign(&sum);
+    }
+}
+
+/// Multiplies the state by a matrix, computing the result in `scratch`, of
+/// the length of the state.
+pub(crate) fn mix<GF>(state: &mut [GF], matrix: &[GF], scratch: &mut [GF])
+where
+    GF: PrimeField,
+{
+    let size = state.len();
+    for (new, row) in scratch.iter_mut().zip(matrix.chunks_exact(size)) {
+        *new = GF::ZERO;
+        for (mij, x) in row.iter().zip(state.iter()) {
+            *new += *mij * x;
+        }
+    }
+    state.copy_from_slice(scratch);
+}
+
+/// Applies the rounds of the Poseidon permutation to a state of any width,
+/// mixing into `scratch`, of the length of the state. Shared by
+/// [`PoseidonPermutation`] and the sponges of [`crate::permutation`].
+///
+/// Full rounds are split around the partial rounds, the second half taking
+/// the extra round of an odd number.
+pub(crate) fn permute_rounds<GF>(
+    state: &mut [GF],
+    scratch: &mut [GF],
+    power: u8,
+    n_full_rounds: usize,
+    n_partial_rounds: usize,
+    mds_matrix: &[GF],
+    round_constants: &[GF],
+) where
+    GF: PrimeField,
+{
+    let rf = n_full_rounds / 2;
+    let rp = n_partial_rounds;
+
+    for i in 0..rf {
+        ark(state, round_constants, i);
+        sbox_full(state, power);
+        mix(state, mds_matrix, scratch);
+    }
+    for i in rf..(rf + rp) {
+        ark(state, round_constants, i);
+        sbox_partial(state, power);
+        mix(state, mds_matrix, scratch);
+    }
+    for i in (rf + rp)..(n_full_rounds + rp) {
+        ark(state, round_constants, i);
+        sbox_full(state, power);
+        mix(state, mds_matrix, scratch);
+    }
+}
+
+/// Applies the rounds of the Poseidon2 permutation, as [`permute_rounds`]
+/// does. Shared by [`Poseidon2Permutation`] and [`crate::poseidon2`].
+pub(crate) fn permute2_rounds<GF>(
+    state: &mut [GF],
+    scratch: &mut [GF],
+    power: u8,
+    n_full_rounds: usize,
+    n_partial_rounds: usize,
+    matrices: (&[GF], &[GF]),
+    round_constants: &[GF],
+) where
+    GF: PrimeField,
+{
+    let (external_matrix, internal_diagonal) = matrices;
+    let width = state.len();
+    let rf = n_full_rounds / 2;
+    let rp = n_partial_rounds;
+
+    mix(state, external_matrix, scratch);
+    for i in 0..rf {
+        ark(state, round_constants, i);
+        sbox_full(state, power);
+        mix(state, external_matrix, scratch);
+    }
+    for i in rf..(rf + rp) {
+        state[0].add_assign(&round_constants[i * width]);
+        sbox_first(state, power);
+        mix_internal(state, internal_diagonal);
+    }
+    for i in (rf + rp)..(n_full_rounds + rp) {
+        ark(state, round_constants, i);
+        sbox_full(state, power);
+        mix(state, external_matrix, scratch);
+    }
+}
+
+/// Poseidon permutation of width T, equal to rate + capacity.
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonPermutation<GF: 'static, const T: usize> {
+    power: u8,
+    rate: usize,
+    output_size: usize,
+    n_partial_rounds: usize,
+    n_full_rounds: usize,
+    mds_matrix: &'static [GF],
+    round_constants: &'static [GF],
+}
+
+impl<GF, const T: usize> PoseidonPermutation<GF, T> {
+    /// Builds the permutation from parameters and their tables.
+    ///
+    /// Panics, at compile time in const contexts, if the width or the tables'
+    /// lengths do not match the parameters.
+    pub const fn new(
+        params: &Parameters,
+        mds_matrix: &'static [GF],
+        round_constants: &'static [GF],
+    ) -> Self {
+        assert!(
+            params.rate + params.capacity == T,
+            "Width must be rate + capacity"
+        );
+        assert!(mds_matrix.len() == T * T, "MDS matrix must be of size T×T");
+        assert!(
+            round_constants.len() == (params.n_full_rounds + params.n_partial_rounds) * T,
+            "Round constants must hold T elements per round"
+        );
+        PoseidonPermutation {
+            power: params.power,
+            rate: params.rate,
+            output_size: params.output_size,
+            n_partial_rounds: params.n_partial_rounds,
+            n_full_rounds: params.n_full_rounds,
+            mds_matrix,
+            round_constants,
+        }
+    }
+
+    pub const fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub const fn output_size(&self) -> usize {
+        self.output_size
+    }
+}
+
+impl<GF, const T: usize> PoseidonPermutation<GF, T>
+where
+    GF: PrimeField,
+{
+    pub fn permute(&self, state: &mut [GF; T]) {
+        permute_rounds(
+            state,
+            &mut [GF::ZERO; T],
+            self.power,
+            self.n_full_rounds,
+            self.n_partial_rounds,
+            self.mds_matrix,
+            self.round_constants,
+        );
+    }
+
+    /// Permutes a state given as a slice, which must hold exactly T elements.
+    pub fn permute_slice(&self, state: &mut [GF]) -> Result<(), HashError> {
+        let length = state.len();
+        let state = state
+            .try_into()
+            .map_err(|_| HashError::StateLength { length, width: T })?;
+        self.permute(state);
+        Ok(())
+    }
+
+    /// Hashes inputs of length a multiple of the rate into `output`, which
+    /// must hold exactly the output size. Same as [`crate::permutation::hash`].
+    pub fn hash(&self, inputs: &[GF], output: &mut [GF]) -> Result<(), HashError> {
+        sponge_hash(self.rate, self.output_size, inputs, output, |state| {
+            self.permute(state)
+        })
+    }
+}
+
+/// Poseidon2 permutation of width T, equal to rate + capacity.
+#[derive(Clone, Copy, Debug)]
+pub struct Poseidon2Permutation<GF: 'static, const T: usize> {
+    power: u8,
+    rate: usize,
+    output_size: usize,
+    n_partial_rounds: usize,
+    n_full_rounds: usize,
+    external_matrix: &'static [GF],
+    internal_diagonal: &'static [GF],
+    round_constants: &'static [GF],
+}
+
+impl<GF, const T: usize> Poseidon2Permutation<GF, T> {
+    /// Builds the permutation from parameters and their tables.
+    ///
+    /// Panics, at compile time in const contexts, if the width or the tables'
+    /// lengths do not match the parameters.
+    pub const fn new(
+        params: &Poseidon2Parameters,
+        external_matrix: &'static [GF],
+        internal_diagonal: &'static [GF],
+        round_constants: &'static [GF],
+    ) -> Self {
+        assert!(
+            params.rate + params.capacity == T,
+            "Width must be rate + capacity"
+        );
+        assert!(
+            external_matrix.len() == T * T,
+            "External matrix must be of size T×T"
+        );
+        assert!(
+            internal_diagonal.len() == T,
+            "Internal diagonal must hold T elements"
+        );
+        assert!(
+            round_constants.len() == (params.n_full_rounds + params.n_partial_rounds) * T,
+            "Round constants must hold T elements per round"
+        );
+        Poseidon2Permutation {
+            power: params.power,
+            rate: params.rate,
+            output_size: params.output_size,
+            n_partial_rounds: params.n_partial_rounds,
+            n_full_rounds: params.n_full_rounds,
+            external_matrix,
+            internal_diagonal,
+            round_constants,
+        }
+    }
+
+    pub const fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub const fn output_size(&self) -> usize {
+        self.output_size
+    }
+}
+
+impl<GF, const T: usize> Poseidon2Permutation<GF, T>
+where
+    GF: PrimeField,
+{
+    /// Same as [`crate::poseidon2::permute`].
+    pub fn permute(&self, state: &mut [GF; T]) {
+        permute2_rounds(
+            state,
+            &mut [GF::ZERO; T],
+            self.power,
+            self.n_full_rounds,
+            self.n_partial_rounds,
+            (self.external_matrix, self.internal_diagonal),
+            self.round_constants,
+        );
+    }
+
+    /// Permutes a state given as a slice, which must hold exactly T elements.
+    pub fn permute_slice(&self, state: &mut [GF]) -> Result<(), HashError> {
+        let length = state.len();
+        let state = state
+            .try_into()
+            .map_err(|_| HashError::StateLength { length, width: T })?;
+        self.permute(state);
+        Ok(())
+    }
+
+    /// Hashes inputs of length a multiple of the rate into `output`, which
+    /// must hold exactly the output size. Same as [`crate::poseidon2::hash`].
+    pub fn hash(&self, inputs: &[GF], output: &mut [GF]) -> Result<(), HashError> {
+        sponge_hash(self.rate, self.output_size, inputs, output, |state| {
+            self.permute(state)
+        })
+    }
+}
+
+/// Absorbs inputs into a zero state of width T, then squeezes `output`, rate
+/// elements per permutation.
+fn sponge_hash<GF, const T: usize>(
+    rate: usize,
+    output_size: usize,
+    inputs: &[GF],
+    output: &mut [GF],
+    permute: impl Fn(&mut [GF; T]),
+) -> Result<(), HashError>
+where
+    GF: PrimeField,
+{
+    if inputs.is_empty() {
+        return Err(HashError::EmptyInputs);
+    }
+    if !inputs.len().is_multiple_of(rate) {
+        return Err(HashError::InputLength {
+            length: inputs.len(),
+            rate,
+        });
+    }
+    if output.len() != output_size {
+        return Err(HashError::OutputLength {
+            length: output.len(),
+            output_size,
+        });
+    }
+
+    let mut state = [GF::ZERO; T];
+    for block in inputs.chunks_exact(rate) {
+        for (x, input) in state.iter_mut().zip(block) {
+            *x += input;
+        }
+        permute(&mut state);
+    }
+    for (i, chunk) in output.chunks_mut(rate).enumerate() {
+        if i > 0 {
+            permute(&mut state);
+        }
+        chunk.copy_from_slice(&state[..chunk.len()]);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_fixed {
+    use super::*;
+    use crate::convert::felts_from_str;
+    use crate::parameters::s128b::{GF, PARAMS, PERMUTATION};
+    use ff::Field;
+
+    #[test]
+    fn test_tables() {
+        let mds_matrix = felts_from_str::<GF>(PARAMS.mds_matrix);
+        let round_constants = felts_from_str::<GF>(PARAMS.round_constants);
+        assert_eq!(PERMUTATION.mds_matrix, &mds_matrix[..]);
+        assert_eq!(PERMUTATION.round_constants, &round_constants[..]);
+    }
+
+    #[test]
+    fn test_permute() {
+        let mut state = [GF::from(7), GF::from(98), GF::from(0)];
+        PERMUTATION.permute(&mut state);
+        let expected = [
+            "11053447091811250430558990262025664436943237361628909971717799705027922243051",
+            "13019049864140962728034369799908465523605511214062523575537207652022843673387",
+            "1746872083923296042231738669780413133264403148587998064386629453972513813613",
+        ];
+        let expected = felts_from_str::<GF>(&expected);
+        assert_eq!(state[..], expected[..]);
+    }
+
+    #[test]
+    fn test_odd_full_rounds() {
+        let mds_matrix = felts_from_str::<GF>(PARAMS.mds_matrix);
+        let round_constants: [GF; 15] = core::array::from_fn(|i| GF::from(i as u64));
+        let mut state = [GF::from(7), GF::from(98), GF::from(0)];
+        let mut expected = state;
+        permute_rounds(
+            &mut state,
+            &mut [GF::ZERO; 3],
+            5,
+            3,
+            2,
+            &mds_matrix,
+            &round_constants,
+        );
+        // One full round, two partial rounds, then the two remaining full rounds.
+        let scratch = &mut [GF::ZERO; 3];
+        for (i, full) in [true, false, false, true, true].into_iter().enumerate() {
+            ark(&mut expected, &round_constants, i);
+            if full {
+                sbox_full(&mut expected, 5);
+            } else {
+                sbox_partial(&mut expected, 5);
+            }
+            mix(&mut expected, &mds_matrix, scratch);
+        }
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn test_hash_errors() {
+        let mut output = [GF::from(0)];
+        assert_eq!(
+            PERMUTATION.hash(&[], &mut output),
+            Err(HashError::EmptyInputs)
+        );
+        assert_eq!(
+            PERMUTATION.hash(&[GF::from(1)], &mut output),
+            Err(HashError::InputLength { length: 1, rate: 2 })
+        );
+        assert_eq!(
+            PERMUTATION.hash(&[GF::from(1), GF::from(2)], &mut []),
+            Err(HashError::OutputLength {
+                length: 0,
+                output_size: 1
+            })
+        );
+        assert_eq!(
+            PERMUTATION.permute_slice(&mut [GF::from(1), GF::from(2)]),
+            Err(HashError::StateLength {
+                length: 2,
+                width: 3
+            })
+        );
+    }
+}
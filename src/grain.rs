@@ -0,0 +1,214 @@
+//! Generation of round constants with the Grain LFSR.
+//!
+//! Follows `generate_parameters_grain.sage` of the Poseidon reference
+//! implementation. An 80-bit LFSR is seeded with the description of the
+//! instance (prime field, x^α S-box, field size, width and numbers of rounds),
+//! run 160 times, then its output bits are taken by pairs: the second bit is
+//! kept if the first one is set, discarded otherwise. Field elements are read
+//! from field-size chunks of output bits, most significant bit first, chunks
+//! not lower than the modulus being rejected.
+//!
+//! ```
+//! use poseidon::grain::round_constants;
+//! use poseidon::parameters::pallas::GF;
+//! let constants = round_constants::<GF>(3, 8, 56);
+//! assert_eq!(constants.len(), (8 + 56) * 3);
+//! ```
+
+use alloc::vec::Vec;
+use ff::PrimeField;
+
+/// The Grain LFSR, seeded with an instance description.
+#[derive(Clone, Debug)]
+pub struct Grain {
+    // Bit i of the sequence is bit i of the integer, bit 0 being the oldest.
+    state: u128,
+}
+
+impl Grain {
+    /// Seeds the LFSR for a prime field of `field_size` bits and a x^α S-box.
+    ///
+    /// Panics if a value does not fit its field of the seed: 12 bits for the
+    /// field size and the width, 10 bits for the numbers of rounds.
+    pub fn new(
+        field_size: usize,
+        width: usize,
+        n_full_rounds: usize,
+        n_partial_rounds: usize,
+    ) -> Self {
+        assert!(field_size < 1 << 12, "Field size must fit in 12 bits");
+        assert!(width < 1 << 12, "Width must fit in 12 bits");
+        assert!(n_full_rounds < 1 << 10, "Full rounds must fit in 10 bits");
+        assert!(
+            n_partial_rounds < 1 << 10,
+            "Partial rounds must fit in 10 bits"
+        );
+
+        let mut grain = Grain { state: 0 };
+        let mut length = 0;
+        let mut push = |value: usize, n_bits: usize| {
+            for i in (0..n_bits).rev() {
+                grain.state |= (((value >> i) & 1) as u128) << length;
+                length += 1;
+            }
+        };
+        // Prime field, x^α S-box.
+        push(1, 2);
+        push(0, 4);
+        push(field_size, 12);
+        push(width, 12);
+        push(n_full_rounds, 10);
+        push(n_partial_rounds, 10);
+        push((1 << 30) - 1, 30);
+
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = self.state;
+        let bit = (s >> 62 ^ s >> 51 ^ s >> 38 ^ s >> 23 ^ s >> 13 ^ s) & 1;
+        self.state = (s >> 1) | (bit << 79);
+        bit == 1
+    }
+
+    /// Returns the next output bit.
+    pub fn next_bit(&mut self) -> bool {
+        while !self.step() {
+            self.step();
+        }
+        self.step()
+    }
+
+    /// Returns the next field element, rejecting integers not lower than the
+    /// modulus.
+    pub fn next_field_element<GF>(&mut self) -> GF
+    where
+        GF: PrimeField,
+    {
+        loop {
+            if let Some(element) = self.next_integer() {
+                return element;
+            }
+        }
+    }
+
+    /// Returns the next field element, reducing integers modulo the modulus, as
+    /// done for the elements of Cauchy matrices.
+    pub fn next_reduced_field_element<GF>(&mut self) -> GF
+    where
+        GF: PrimeField,
+    {
+        let mut element = GF::ZERO;
+        for _ in 0..GF::NUM_BITS {
+            element = element.double();
+            if self.next_bit() {
+                element += GF::ONE;
+            }
+        }
+        element
+    }
+
+    /// Reads an integer of `GF::NUM_BITS` bits, if lower than the modulus.
+    fn next_integer<GF>(&mut self) -> Option<GF>
+    where
+        GF: PrimeField,
+    {
+        let mut repr = GF::Repr::default();
+        let bytes = repr.as_mut();
+        let n_bits = GF::NUM_BITS as usize;
+        let skipped = bytes.len() * 8 - n_bits;
+        // Fill big-endian, then fix the order for little-endian fields.
+        for i in skipped..bytes.len() * 8 {
+            if self.next_bit() {
+                bytes[i / 8] |= 0x80 >> (i % 8);
+            }
+        }
+        if GF::ONE.to_repr().as_ref()[0] == 1 {
+            bytes.reverse();
+        }
+        GF::from_repr(repr).into()
+    }
+}
+
+/// Generates the (R_F + R_P)·t round constants of a Poseidon instance.
+pub fn round_constants<GF>(width: usize, n_full_rounds: usize, n_partial_rounds: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut grain = Grain::new(
+        GF::NUM_BITS as usize,
+        width,
+        n_full_rounds,
+        n_partial_rounds,
+    );
+    (0..(n_full_rounds + n_partial_rounds) * width)
+        .map(|_| grain.next_field_element())
+        .collect()
+}
+
+/// Generates the round constants of a Poseidon2 instance, t per round.
+///
+/// Partial rounds only use one constant, the others are set to zero as in
+/// [`crate::parameters::Poseidon2Parameters`].
+pub fn poseidon2_round_constants<GF>(
+    width: usize,
+    n_full_rounds: usize,
+    n_partial_rounds: usize,
+) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut grain = Grain::new(
+        GF::NUM_BITS as usize,
+        width,
+        n_full_rounds,
+        n_partial_rounds,
+    );
+    let rf = n_full_rounds / 2;
+    let mut constants = Vec::with_capacity((n_full_rounds + n_partial_rounds) * width);
+    for round in 0..(n_full_rounds + n_partial_rounds) {
+        if round < rf || round >= rf + n_partial_rounds {
+            constants.extend((0..width).map(|_| grain.next_field_element::<GF>()));
+        } else {
+            constants.push(grain.next_field_element());
+            constants.resize(constants.len() + width - 1, GF::ZERO);
+        }
+    }
+    constants
+}
+
+#[cfg(test)]
+mod test_grain {
+    use super::*;
+    use crate::convert::felts_from_str;
+    use crate::parameters::pallas::GF;
+    use crate::parameters::poseidon2::pallas::PARAMS;
+
+    #[test]
+    fn test_round_constants() {
+        // First constants of the reference script for the Pallas field with
+        // t = 3, R_F = 8, R_P = 56.
+        let expected = [
+            "24448666467656506447555018649749346340705294023832615387641453784702583464707",
+            "19752610610343814834081989345964253902282700341539483876504601969121084774539",
+            "9520793415506326549109545537894287560752519598132096386048093015534488804808",
+            "22814234098357034097599682726494820560934925862581927123816510593532324971186",
+        ];
+        let constants = round_constants::<GF>(3, 8, 56);
+        assert_eq!(constants.len(), 192);
+        assert_eq!(constants[..4], felts_from_str::<GF>(&expected)[..]);
+    }
+
+    #[test]
+    fn test_poseidon2_round_constants() {
+        let constants = poseidon2_round_constants::<GF>(
+            PARAMS.rate + PARAMS.capacity,
+            PARAMS.n_full_rounds,
+            PARAMS.n_partial_rounds,
+        );
+        assert_eq!(constants, felts_from_str::<GF>(PARAMS.round_constants));
+    }
+}
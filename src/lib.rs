@@ -5,8 +5,20 @@
 //! Multiple sets of parameters are included in the crate. However, if users
 //! wish so, they can provide their own set of parameters.
 //!
-//! Hash functions are exported through a C ABI in a shared library.
-//! This allows the functions to be called in geth from golang.
+//! Hash functions are exported through a C ABI in a static library.
+//! This allows the functions to be called in geth from golang, see [`ffi`].
+//!
+//! # Features
+//! - `std` (default): links the standard library. Without it the crate is
+//!   `no_std`.
+//! - `alloc`, implied by `std`: everything but the permutations over static
+//!   tables of [`fixed`] and the `hash` function of each set of parameters,
+//!   which neither allocate nor return allocated errors, and are all that
+//!   remains without it.
+//! - `parallel`: hashes batches in parallel, see [`hash_batch`]. Implies `std`.
+//! - `serde`: loads sets of parameters from files, see `parameters::file`.
+//! - `c-lib`: installs a libc global allocator and an aborting panic handler,
+//!   for building the C library without `std`.
 //!
 //! # Examples
 //! Hash functions are named hash_<params>, where <params> is the name of the
@@ -20,51 +32,144 @@
 //! let inputs = vec![GF::from(7), GF::from(54)];
 //! let h = hash_s128b(&inputs);
 //! ```
+//!
+//! Inputs to hash_<params> must have a length multiple of the rate. Messages
+//! of arbitrary length are hashed with hash_var_len_<params>, which pads them
+//! with a sponge-compliant rule:
+//!
+//! ```
+//! use poseidon::hash_var_len_s128b;
+//! use poseidon::parameters::s128b::GF;
+//! let inputs = vec![GF::from(7), GF::from(54), GF::from(3)];
+//! let h = hash_var_len_s128b(&inputs);
+//! ```
+//!
+//! The bare permutation is available as permute_<params>, operating in place
+//! on a state of rate + capacity elements:
+//!
+//! ```
+//! use poseidon::permute_sw3;
+//! use poseidon::parameters::sw3::GF;
+//! let mut state = vec![GF::from(1), GF::from(2), GF::from(3), GF::from(0)];
+//! permute_sw3(&mut state).unwrap();
+//! ```
+//!
+//! The Poseidon2 permutation is available for some fields as
+//! hash_poseidon2_<params> and permute_poseidon2_<params>, see [`poseidon2`].
+//!
+//! Generic functions such as [`hash`] take either [`parameters::Parameters`],
+//! whose constants are parsed on every call, or [`parameters::PreparedParameters`].
+//! Each set of parameters caches its prepared parameters:
+//!
+//! ```
+//! use poseidon::hash;
+//! use poseidon::parameters::s128b::{prepared, GF};
+//! let inputs = vec![GF::from(7), GF::from(54)];
+//! let h = hash(&inputs, prepared()).unwrap();
+//! ```
+//!
+//! Messages of a common length are hashed at once with [`hash_batch`], in
+//! parallel with the `parallel` feature:
+//!
+//! ```
+//! use poseidon::hash_batch;
+//! use poseidon::parameters::s128b::{prepared, GF};
+//! let messages = vec![GF::from(7), GF::from(54), GF::from(8), GF::from(55)];
+//! let hashes = hash_batch(&messages, 2, prepared()).unwrap();
+//! ```
 
 // Implementation is done for PrimeFields.
 // Question remains of how to handle BinaryFields.
 // Other fields are probably not useful at this point.
-#![cfg_attr(
-    any(target_arch = "wasm32", not(feature = "std")),
-    no_std,
-    feature(alloc_error_handler)
-)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(feature = "alloc")]
 #[macro_use]
 extern crate alloc;
 
-use alloc::alloc::*;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use ff::Field;
 
+/// Global allocator of the C library built without std, backed by libc.
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
 #[derive(Default)]
 pub struct Allocator;
 
-unsafe impl GlobalAlloc for Allocator {
-    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
-        libc::malloc(layout.size()) as *mut u8
+/// Alignment guaranteed by malloc on 64-bit targets.
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
+const MIN_ALIGN: usize = 16;
+
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
+unsafe impl alloc::alloc::GlobalAlloc for Allocator {
+    unsafe fn alloc(&self, layout: alloc::alloc::Layout) -> *mut u8 {
+        if layout.align() <= MIN_ALIGN {
+            return libc::malloc(layout.size()) as *mut u8;
+        }
+        let mut ptr = core::ptr::null_mut();
+        if libc::posix_memalign(&mut ptr, layout.align(), layout.size()) != 0 {
+            return core::ptr::null_mut();
+        }
+        ptr as *mut u8
     }
-    unsafe fn dealloc(&self, ptr: *mut u8, _layout: Layout) {
+    unsafe fn dealloc(&self, ptr: *mut u8, _layout: alloc::alloc::Layout) {
         libc::free(ptr as *mut libc::c_void);
     }
 }
 
-#[cfg(not(test))]
-#[alloc_error_handler]
-fn alloc_error_handler(layout: alloc::alloc::Layout) -> ! {
-    panic!("Allocation error: {:?}", layout)
-}
-
 /// The static global allocator.
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
 #[global_allocator]
 static GLOBAL_ALLOCATOR: Allocator = Allocator;
 
+#[cfg(feature = "alloc")]
 pub mod convert;
-use convert::{felts_from_u8s, u8s_from_felts};
 
 pub mod fields;
 
+#[cfg(feature = "alloc")]
 pub mod permutation;
-pub use permutation::{hash, Poseidon};
+#[cfg(feature = "alloc")]
+pub use permutation::{hash, hash_batch, hash_var_len, permute, Poseidon, SpongeMode};
+
+pub mod fixed;
+pub use fixed::{HashError, Poseidon2Permutation, PoseidonPermutation};
+
+#[cfg(feature = "alloc")]
+pub mod optimized;
+#[cfg(feature = "alloc")]
+pub use optimized::{permute_optimized, OptimizedParameters};
+
+#[cfg(feature = "alloc")]
+pub mod sponge;
+#[cfg(feature = "alloc")]
+pub use sponge::{Sponge, SpongeError, SpongeOp};
+
+#[cfg(feature = "alloc")]
+pub mod poseidon2;
+#[cfg(feature = "alloc")]
+pub use poseidon2::Poseidon2;
+
+#[cfg(feature = "alloc")]
+pub mod grain;
+
+#[cfg(feature = "alloc")]
+pub mod mds;
+
+#[cfg(feature = "alloc")]
+pub mod security;
+
+#[cfg(feature = "alloc")]
+pub mod precompile;
+
+#[cfg(feature = "alloc")]
+pub mod cache;
+#[cfg(feature = "alloc")]
+pub use precompile::PrecompileError;
+
+#[cfg(feature = "alloc")]
+pub mod ffi;
 
 pub mod parameters;
 pub use parameters::pallas;
@@ -76,90 +181,175 @@ pub use parameters::sw8;
 pub use parameters::vesta;
 // add more parameters here.
 
+#[cfg(feature = "alloc")]
 pub fn hash_s128b(inputs: &[s128b::GF]) -> Vec<s128b::GF> {
-    hash::<s128b::GF>(inputs, &s128b::PARAMS).unwrap()
+    let mut output = vec![s128b::GF::ZERO; s128b::PARAMS.output_size];
+    s128b::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
 }
 
-// C-Interface for the hash function
-#[no_mangle]
-pub extern "C" fn c_hash_s128b(
-    input: *const u8,
-    input_len: usize,
-    output: *mut u8,
-    output_len: usize,
-) -> usize {
-    let input = unsafe {
-        assert!(!input.is_null());
-        core::slice::from_raw_parts(input, input_len)
-    };
-    let input = felts_from_u8s(&input);
-
-    let result = hash_s128b(&input);
-    let result = u8s_from_felts(&result);
-
-    let count = result.len().min(output_len);
-    // let src = result.as_ptr();
-    let output = unsafe {
-        assert!(!output.is_null());
-        core::slice::from_raw_parts_mut(output, output_len)
-    };
-    output.copy_from_slice(&result);
-    count
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_s128b(inputs: &[s128b::GF]) -> Vec<s128b::GF> {
+    hash_var_len(inputs, s128b::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_s128b(state: &mut [s128b::GF]) -> Result<(), HashError> {
+    s128b::PERMUTATION.permute_slice(state)
 }
 
+#[cfg(feature = "alloc")]
 pub fn hash_sw2(inputs: &[sw2::GF]) -> Vec<sw2::GF> {
-    hash::<sw2::GF>(inputs, &sw2::PARAMS).unwrap()
+    let mut output = vec![sw2::GF::ZERO; sw2::PARAMS.output_size];
+    sw2::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
 }
 
-// C-Interface for the hash function
-#[no_mangle]
-pub extern "C" fn c_hash_sw2(
-    input: *const u8,
-    input_len: usize,
-    output: *mut u8,
-    output_len: usize,
-) -> usize {
-    let input = unsafe {
-        assert!(!input.is_null());
-        core::slice::from_raw_parts(input, input_len)
-    };
-    let input = felts_from_u8s(&input);
-
-    let result = hash_sw2(&input);
-    let result = u8s_from_felts(&result);
-
-    let count = result.len().min(output_len);
-    // let src = result.as_ptr();
-    let output = unsafe {
-        assert!(!output.is_null());
-        core::slice::from_raw_parts_mut(output, output_len)
-    };
-    output.copy_from_slice(&result);
-    count
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_sw2(inputs: &[sw2::GF]) -> Vec<sw2::GF> {
+    hash_var_len(inputs, sw2::prepared())
 }
 
+#[cfg(feature = "alloc")]
+pub fn permute_sw2(state: &mut [sw2::GF]) -> Result<(), HashError> {
+    sw2::PERMUTATION.permute_slice(state)
+}
+
+#[cfg(feature = "alloc")]
 pub fn hash_sw3(inputs: &[sw3::GF]) -> Vec<sw3::GF> {
-    hash::<sw3::GF>(inputs, &sw3::PARAMS).unwrap()
+    let mut output = vec![sw3::GF::ZERO; sw3::PARAMS.output_size];
+    sw3::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
 }
 
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_sw3(inputs: &[sw3::GF]) -> Vec<sw3::GF> {
+    hash_var_len(inputs, sw3::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_sw3(state: &mut [sw3::GF]) -> Result<(), HashError> {
+    sw3::PERMUTATION.permute_slice(state)
+}
+
+#[cfg(feature = "alloc")]
 pub fn hash_sw4(inputs: &[sw4::GF]) -> Vec<sw4::GF> {
-    hash::<sw4::GF>(inputs, &sw4::PARAMS).unwrap()
+    let mut output = vec![sw4::GF::ZERO; sw4::PARAMS.output_size];
+    sw4::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_sw4(inputs: &[sw4::GF]) -> Vec<sw4::GF> {
+    hash_var_len(inputs, sw4::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_sw4(state: &mut [sw4::GF]) -> Result<(), HashError> {
+    sw4::PERMUTATION.permute_slice(state)
 }
 
+#[cfg(feature = "alloc")]
 pub fn hash_sw8(inputs: &[sw8::GF]) -> Vec<sw8::GF> {
-    hash::<sw8::GF>(inputs, &sw8::PARAMS).unwrap()
+    let mut output = vec![sw8::GF::ZERO; sw8::PARAMS.output_size];
+    sw8::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_sw8(inputs: &[sw8::GF]) -> Vec<sw8::GF> {
+    hash_var_len(inputs, sw8::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_sw8(state: &mut [sw8::GF]) -> Result<(), HashError> {
+    sw8::PERMUTATION.permute_slice(state)
 }
 
+#[cfg(feature = "alloc")]
 pub fn hash_pallas(inputs: &[pallas::GF]) -> Vec<pallas::GF> {
-    hash::<pallas::GF>(inputs, &pallas::PARAMS).unwrap()
+    let mut output = vec![pallas::GF::ZERO; pallas::PARAMS.output_size];
+    pallas::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_pallas(inputs: &[pallas::GF]) -> Vec<pallas::GF> {
+    hash_var_len(inputs, pallas::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_pallas(state: &mut [pallas::GF]) -> Result<(), HashError> {
+    pallas::PERMUTATION.permute_slice(state)
 }
 
+#[cfg(feature = "alloc")]
 pub fn hash_vesta(inputs: &[vesta::GF]) -> Vec<vesta::GF> {
-    hash::<vesta::GF>(inputs, &vesta::PARAMS).unwrap()
+    let mut output = vec![vesta::GF::ZERO; vesta::PARAMS.output_size];
+    vesta::PERMUTATION.hash(inputs, &mut output).unwrap();
+    output
 }
 
-#[cfg(not(test))]
+#[cfg(feature = "alloc")]
+pub fn hash_var_len_vesta(inputs: &[vesta::GF]) -> Vec<vesta::GF> {
+    hash_var_len(inputs, vesta::prepared())
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_vesta(state: &mut [vesta::GF]) -> Result<(), HashError> {
+    vesta::PERMUTATION.permute_slice(state)
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_poseidon2_pallas(inputs: &[pallas::GF]) -> Vec<pallas::GF> {
+    let mut output = vec![pallas::GF::ZERO; parameters::poseidon2::pallas::OUTPUT_SIZE];
+    parameters::poseidon2::pallas::PERMUTATION
+        .hash(inputs, &mut output)
+        .unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_poseidon2_pallas(state: &mut [pallas::GF]) -> Result<(), HashError> {
+    parameters::poseidon2::pallas::PERMUTATION.permute_slice(state)
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_poseidon2_stark252(inputs: &[sw3::GF]) -> Vec<sw3::GF> {
+    let mut output = vec![sw3::GF::ZERO; parameters::poseidon2::stark252::OUTPUT_SIZE];
+    parameters::poseidon2::stark252::PERMUTATION
+        .hash(inputs, &mut output)
+        .unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_poseidon2_stark252(state: &mut [sw3::GF]) -> Result<(), HashError> {
+    parameters::poseidon2::stark252::PERMUTATION.permute_slice(state)
+}
+
+#[cfg(feature = "alloc")]
+pub fn hash_poseidon2_vesta(inputs: &[vesta::GF]) -> Vec<vesta::GF> {
+    let mut output = vec![vesta::GF::ZERO; parameters::poseidon2::vesta::OUTPUT_SIZE];
+    parameters::poseidon2::vesta::PERMUTATION
+        .hash(inputs, &mut output)
+        .unwrap();
+    output
+}
+
+#[cfg(feature = "alloc")]
+pub fn permute_poseidon2_vesta(state: &mut [vesta::GF]) -> Result<(), HashError> {
+    parameters::poseidon2::vesta::PERMUTATION.permute_slice(state)
+}
+
+/// Aborts the process, as unwinding through the C-Interface is not possible.
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
 #[panic_handler]
 pub fn panic(_info: &core::panic::PanicInfo) -> ! {
-    loop {}
+    unsafe { libc::abort() }
 }
+
+/// Referenced by the precompiled `alloc`, never called as panics abort.
+#[cfg(all(feature = "c-lib", not(feature = "std")))]
+#[no_mangle]
+extern "C" fn rust_eh_personality() {}
@@ -0,0 +1,540 @@
+//! Generation of MDS matrices and checks against infinitely long subspace
+//! trails.
+//!
+//! Follows `generate_parameters_grain.sage` of the Poseidon reference
+//! implementation: Cauchy matrices (x_i + y_j)^-1 are sampled from the Grain
+//! LFSR until one passes algorithms 1 to 3 of "Proving resistance against
+//! infinitely long subspace trails: how to choose the linear layer". With a
+//! single S-box in partial rounds:
+//!
+//! - Algorithm 1 rejects matrices M such that, for some i < t, M^i is a
+//!   multiple of the identity, or the subspace left inactive by i partial
+//!   rounds contains an eigenvector of M^i or is invariant under some M^j.
+//! - Algorithm 2 rejects matrices under which the S-box coordinate does not
+//!   generate the whole space, leaving an invariant subspace.
+//! - Algorithm 3 applies algorithm 2 to M^r, for 2 ≤ r ≤ 4t.
+//!
+//! The reference applies the partial S-box to the first element of the state,
+//! whereas [`crate::permutation`] applies it to the last one. The checks are
+//! run on the matrix with reversed coordinates, so that they hold for matrices
+//! as used by the crate.
+//!
+//! ```
+//! use poseidon::convert::str_from_felts;
+//! use poseidon::grain::Grain;
+//! use poseidon::mds::{generate_mds_matrix, is_secure};
+//! use poseidon::parameters::pallas::GF;
+//! let mut grain = Grain::new(255, 3, 8, 56);
+//! let mds_matrix = generate_mds_matrix::<GF>(&mut grain, 3);
+//! assert!(is_secure(&mds_matrix, 3));
+//! // Decimal strings, as in `Parameters::mds_matrix`.
+//! let mds_matrix = str_from_felts(&mds_matrix);
+//! ```
+
+use crate::convert::le_bytes;
+use crate::grain::Grain;
+use crate::optimized::mat_mul;
+use alloc::vec::Vec;
+use ff::PrimeField;
+
+/// Samples a t×t Cauchy matrix, row by row.
+///
+/// The 2t elements x_i and y_j are resampled until they are distinct and no
+/// sum x_i + y_j is zero, which makes the matrix MDS.
+pub fn cauchy_matrix<GF>(grain: &mut Grain, width: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    loop {
+        let mut elements: Vec<GF>;
+        loop {
+            elements = (0..2 * width)
+                .map(|_| grain.next_reduced_field_element())
+                .collect();
+            let distinct = (0..elements.len()).all(|i| !elements[i + 1..].contains(&elements[i]));
+            if distinct {
+                break;
+            }
+        }
+        let (xs, ys) = elements.split_at(width);
+        let matrix: Option<Vec<GF>> = xs
+            .iter()
+            .flat_map(|x| ys.iter().map(move |y| Option::from((*x + y).invert())))
+            .collect();
+        if let Some(matrix) = matrix {
+            return matrix;
+        }
+    }
+}
+
+/// Samples Cauchy matrices until one passes [`is_secure`].
+pub fn generate_mds_matrix<GF>(grain: &mut Grain, width: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    loop {
+        let matrix = cauchy_matrix(grain, width);
+        if is_secure(&matrix, width) {
+            return matrix;
+        }
+    }
+}
+
+/// Returns whether a t×t matrix passes algorithms 1, 2 and 3.
+pub fn is_secure<GF>(mds_matrix: &[GF], width: usize) -> bool
+where
+    GF: PrimeField,
+{
+    algorithm_1(mds_matrix, width)
+        && algorithm_2(mds_matrix, width)
+        && algorithm_3(mds_matrix, width)
+}
+
+/// Returns whether a t×t matrix passes algorithm 1: no subspace left inactive
+/// by up to t - 1 partial rounds is invariant.
+pub fn algorithm_1<GF>(mds_matrix: &[GF], width: usize) -> bool
+where
+    GF: PrimeField,
+{
+    let t = width;
+    let m = reversed(mds_matrix, t);
+    // powers[i] = M^(i + 1)
+    let mut powers = vec![m.clone()];
+    for i in 1..t {
+        powers.push(mat_mul(&powers[i - 1], &m, t));
+    }
+
+    for i in 1..t {
+        let power = &powers[i - 1];
+        let is_scalar =
+            (0..t * t).all(|k| power[k] == if k % (t + 1) == 0 { power[0] } else { GF::ZERO });
+        if is_scalar {
+            return false;
+        }
+
+        let inactive = inactive_subspace(&powers, i, t);
+        let mut eigenvectors = Vec::new();
+        for eigenvalue in roots(&charpoly(power, t)) {
+            let mut shifted = power.clone();
+            for k in 0..t {
+                shifted[k * t + k] -= eigenvalue;
+            }
+            let eigenspace = kernel(shifted.chunks(t).map(|row| row.to_vec()).collect(), t);
+            eigenvectors.extend(intersection(&inactive, &eigenspace, t));
+        }
+        let invariant = span(eigenvectors, t);
+        if !invariant.is_empty() && invariant.len() != t {
+            return false;
+        }
+
+        if powers[..i]
+            .iter()
+            .any(|power| image(power, &inactive, t) == inactive)
+        {
+            return false;
+        }
+    }
+    true
+}
+
+/// Returns whether a t×t matrix passes algorithm 2: the S-box coordinate
+/// generates the whole space.
+pub fn algorithm_2<GF>(mds_matrix: &[GF], width: usize) -> bool
+where
+    GF: PrimeField,
+{
+    is_cyclic(&reversed(mds_matrix, width), width)
+}
+
+/// Returns whether a t×t matrix passes algorithm 3: its powers M^r, for
+/// 2 ≤ r ≤ 4t, pass algorithm 2.
+pub fn algorithm_3<GF>(mds_matrix: &[GF], width: usize) -> bool
+where
+    GF: PrimeField,
+{
+    let m = reversed(mds_matrix, width);
+    let mut power = m.clone();
+    for _ in 2..=4 * width {
+        power = mat_mul(&power, &m, width);
+        if !is_cyclic(&power, width) {
+            return false;
+        }
+    }
+    true
+}
+
+/// Reverses the coordinates, moving the S-box coordinate of the crate first.
+fn reversed<GF>(matrix: &[GF], t: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    matrix[..t * t].iter().rev().copied().collect()
+}
+
+fn apply<GF>(matrix: &[GF], v: &[GF]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    matrix
+        .chunks(v.len())
+        .map(|row| row.iter().zip(v).fold(GF::ZERO, |acc, (a, b)| acc + *a * b))
+        .collect()
+}
+
+fn unit<GF>(i: usize, t: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut v = vec![GF::ZERO; t];
+    v[i] = GF::ONE;
+    v
+}
+
+/// Returns whether the first coordinate generates the whole space under m.
+fn is_cyclic<GF>(m: &[GF], t: usize) -> bool
+where
+    GF: PrimeField,
+{
+    let mut v = unit(0, t);
+    let mut subspace = span(vec![v.clone()], t);
+    loop {
+        let dimension = subspace.len();
+        v = apply(m, &v);
+        subspace.push(v.clone());
+        subspace = span(subspace, t);
+        if subspace.len() == t {
+            return true;
+        }
+        if subspace.len() <= dimension {
+            return false;
+        }
+    }
+}
+
+/// Subspace of the differences going through i partial rounds without
+/// activating the S-box, given powers[j] = M^(j + 1).
+fn inactive_subspace<GF>(powers: &[Vec<GF>], i: usize, t: usize) -> Vec<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    if i == 1 {
+        return (1..t).map(|k| unit(k, t)).collect();
+    }
+    let conditions = powers[..i - 1]
+        .iter()
+        .map(|power| power[1..t].to_vec())
+        .collect();
+    let vectors = kernel(conditions, t - 1)
+        .into_iter()
+        .map(|v| core::iter::once(GF::ZERO).chain(v).collect())
+        .collect();
+    span(vectors, t)
+}
+
+/// Reduced row echelon basis of the span of vectors of length t.
+fn span<GF>(mut rows: Vec<Vec<GF>>, t: usize) -> Vec<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    let mut rank = 0;
+    for col in 0..t {
+        let pivot = match (rank..rows.len()).find(|&r| !bool::from(rows[r][col].is_zero())) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+        let scale = rows[rank][col].invert().unwrap();
+        rows[rank].iter_mut().for_each(|x| *x *= scale);
+        let pivot_row = rows[rank].clone();
+        for (r, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if r != rank && !bool::from(factor.is_zero()) {
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+        rank += 1;
+    }
+    rows.truncate(rank);
+    rows
+}
+
+/// Basis of the vectors of length t orthogonal to all rows.
+fn kernel<GF>(rows: Vec<Vec<GF>>, t: usize) -> Vec<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    let rows = span(rows, t);
+    let pivots: Vec<usize> = rows
+        .iter()
+        .map(|row| row.iter().position(|x| !bool::from(x.is_zero())).unwrap())
+        .collect();
+    (0..t)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut v = unit(free, t);
+            for (row, &pivot) in rows.iter().zip(&pivots) {
+                v[pivot] = -row[free];
+            }
+            v
+        })
+        .collect()
+}
+
+/// Intersection of two subspaces, as the orthogonal of the sum of their
+/// orthogonals.
+fn intersection<GF>(a: &[Vec<GF>], b: &[Vec<GF>], t: usize) -> Vec<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    let mut orthogonals = kernel(a.to_vec(), t);
+    orthogonals.extend(kernel(b.to_vec(), t));
+    span(kernel(orthogonals, t), t)
+}
+
+fn image<GF>(matrix: &[GF], subspace: &[Vec<GF>], t: usize) -> Vec<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    span(subspace.iter().map(|v| apply(matrix, v)).collect(), t)
+}
+
+/// Characteristic polynomial of a t×t matrix (Faddeev-LeVerrier), with
+/// coefficients from the constant term up.
+fn charpoly<GF>(a: &[GF], t: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut coeffs = vec![GF::ZERO; t + 1];
+    coeffs[t] = GF::ONE;
+    let mut m = vec![GF::ZERO; t * t];
+    for k in 1..=t {
+        m = mat_mul(a, &m, t);
+        for i in 0..t {
+            m[i * t + i] += coeffs[t - k + 1];
+        }
+        let am = mat_mul(a, &m, t);
+        let trace = (0..t).fold(GF::ZERO, |acc, i| acc + am[i * t + i]);
+        coeffs[t - k] = -trace * GF::from(k as u64).invert().unwrap();
+    }
+    coeffs
+}
+
+// Polynomials are vectors of coefficients from the constant term up, without
+// trailing zeros.
+
+fn trim<GF>(mut p: Vec<GF>) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    while p.last().is_some_and(|c| bool::from(c.is_zero())) {
+        p.pop();
+    }
+    p
+}
+
+fn poly_sub<GF>(a: &[GF], b: &[GF]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut result = vec![GF::ZERO; a.len().max(b.len())];
+    for (i, r) in result.iter_mut().enumerate() {
+        *r = a.get(i).copied().unwrap_or(GF::ZERO) - b.get(i).copied().unwrap_or(GF::ZERO);
+    }
+    trim(result)
+}
+
+/// Euclidean division by a non-zero polynomial.
+fn poly_divrem<GF>(a: &[GF], b: &[GF]) -> (Vec<GF>, Vec<GF>)
+where
+    GF: PrimeField,
+{
+    let mut remainder = a.to_vec();
+    if a.len() < b.len() {
+        return (Vec::new(), remainder);
+    }
+    let lead_inv = b[b.len() - 1].invert().unwrap();
+    let mut quotient = vec![GF::ZERO; a.len() - b.len() + 1];
+    for i in (0..quotient.len()).rev() {
+        let factor = remainder[i + b.len() - 1] * lead_inv;
+        quotient[i] = factor;
+        for (j, c) in b.iter().enumerate() {
+            remainder[i + j] -= factor * c;
+        }
+    }
+    (trim(quotient), trim(remainder))
+}
+
+fn poly_mulmod<GF>(a: &[GF], b: &[GF], f: &[GF]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![GF::ZERO; a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += *x * y;
+        }
+    }
+    poly_divrem(&product, f).1
+}
+
+/// Computes base^e mod f, the bits of e being given most significant first.
+fn poly_powmod<GF>(base: &[GF], exponent: &[bool], f: &[GF]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut result = poly_divrem(&[GF::ONE], f).1;
+    for &bit in exponent {
+        result = poly_mulmod(&result, &result, f);
+        if bit {
+            result = poly_mulmod(&result, base, f);
+        }
+    }
+    result
+}
+
+/// Monic greatest common divisor.
+fn poly_gcd<GF>(mut a: Vec<GF>, mut b: Vec<GF>) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    while !b.is_empty() {
+        let remainder = poly_divrem(&a, &b).1;
+        a = b;
+        b = remainder;
+    }
+    let lead_inv = a[a.len() - 1].invert().unwrap();
+    a.iter().map(|c| *c * lead_inv).collect()
+}
+
+/// Bits of p - 1, most significant first.
+fn modulus_minus_one_bits<GF>() -> Vec<bool>
+where
+    GF: PrimeField,
+{
+    let bytes = le_bytes(&-GF::ONE);
+    let bits: Vec<bool> = bytes
+        .iter()
+        .rev()
+        .flat_map(|byte| (0..8).rev().map(move |i| (byte >> i) & 1 == 1))
+        .collect();
+    let start = bits.iter().position(|&bit| bit).unwrap_or(bits.len());
+    bits[start..].to_vec()
+}
+
+/// Roots in the field of a non-zero polynomial, without multiplicity.
+fn roots<GF>(f: &[GF]) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let x = [GF::ZERO, GF::ONE];
+    let p_minus_one = modulus_minus_one_bits::<GF>();
+    // gcd(f, x^p - x) is the product of the linear factors of f.
+    let x_p = poly_mulmod(&poly_powmod(&x, &p_minus_one, f), &x, f);
+    let linear = poly_gcd(f.to_vec(), poly_sub(&x_p, &x));
+    let mut roots = Vec::new();
+    split_linear(linear, &p_minus_one[..p_minus_one.len() - 1], &mut roots);
+    roots
+}
+
+/// Finds the roots of a product of distinct linear factors (Cantor-Zassenhaus),
+/// given the bits of (p - 1) / 2.
+fn split_linear<GF>(f: Vec<GF>, half: &[bool], roots: &mut Vec<GF>)
+where
+    GF: PrimeField,
+{
+    match f.len() {
+        0 | 1 => {}
+        2 => roots.push(-f[0] * f[1].invert().unwrap()),
+        _ => {
+            // (x + a)^((p - 1) / 2) - 1 vanishes on about half of the roots.
+            for a in 0u64.. {
+                let h = poly_powmod(&[GF::from(a), GF::ONE], half, &f);
+                let factor = poly_gcd(f.clone(), poly_sub(&h, &[GF::ONE]));
+                if factor.len() > 1 && factor.len() < f.len() {
+                    let cofactor = poly_divrem(&f, &factor).0;
+                    split_linear(factor, half, roots);
+                    split_linear(cofactor, half, roots);
+                    return;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_mds {
+    use super::*;
+    use crate::convert::str_from_felts;
+    use crate::optimized::invert;
+    use crate::parameters::pallas::GF;
+    use ff::Field;
+
+    #[test]
+    fn test_roots() {
+        // (x - 2)(x - 5)^2
+        let f = [-GF::from(50), GF::from(45), -GF::from(12), GF::ONE];
+        let found = roots(&f);
+        assert_eq!(found.len(), 2);
+        assert!(found.contains(&GF::from(2)) && found.contains(&GF::from(5)));
+        // x^2 - a has roots if and only if a is a square.
+        for a in 2..10u64 {
+            let f = [-GF::from(a), GF::ZERO, GF::ONE];
+            let is_square = bool::from(GF::from(a).sqrt().is_some());
+            assert_eq!(roots(&f).len(), if is_square { 2 } else { 0 });
+        }
+    }
+
+    #[test]
+    fn test_charpoly() {
+        // [[2, 1], [1, 2]] has characteristic polynomial x^2 - 4x + 3.
+        let a = [GF::from(2), GF::ONE, GF::ONE, GF::from(2)];
+        assert_eq!(charpoly(&a, 2), vec![GF::from(3), -GF::from(4), GF::ONE]);
+    }
+
+    #[test]
+    fn test_cauchy_matrix() {
+        let mut grain = Grain::new(255, 3, 8, 56);
+        let matrix = cauchy_matrix::<GF>(&mut grain, 3);
+        assert!(invert(&matrix, 3).is_some());
+        let inverses: Vec<GF> = matrix.iter().map(|x| x.invert().unwrap()).collect();
+        // 1/M_ij - 1/M_i0 does not depend on i.
+        for i in 0..3 {
+            for j in 0..3 {
+                assert_eq!(
+                    inverses[i * 3 + j] - inverses[i * 3],
+                    inverses[j] - inverses[0]
+                );
+            }
+        }
+        for (decimal, x) in str_from_felts(&matrix).iter().zip(&matrix) {
+            assert_eq!(GF::from_str_vartime(decimal).as_ref(), Some(x));
+        }
+    }
+
+    #[test]
+    fn test_generate_mds_matrix() {
+        let mut grain = Grain::new(255, 5, 8, 56);
+        let matrix = generate_mds_matrix::<GF>(&mut grain, 5);
+        assert!(is_secure(&matrix, 5));
+    }
+
+    #[test]
+    fn test_insecure() {
+        let matrix = |entries: [u64; 9]| entries.map(GF::from);
+        // 1 + I keeps span(e_t, 1) invariant.
+        let ones_plus_identity = matrix([2, 1, 1, 1, 2, 1, 1, 1, 2]);
+        assert!(!algorithm_2(&ones_plus_identity, 3));
+        assert!(!is_secure(&ones_plus_identity, 3));
+        // e_1 is an eigenvector that never reaches the S-box.
+        let eigenvector = matrix([1, 1, 2, 0, 2, 1, 0, 1, 3]);
+        assert!(algorithm_2(&eigenvector, 3));
+        assert!(!algorithm_1(&eigenvector, 3));
+        // The identity is a multiple of itself.
+        assert!(!algorithm_1(&matrix([1, 0, 0, 0, 1, 0, 0, 0, 1]), 3));
+    }
+}
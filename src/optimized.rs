@@ -0,0 +1,401 @@
+//! Optimized evaluation of the Poseidon permutation.
+//!
+//! Follows appendix B of the Poseidon paper, as done in neptune. Partial rounds
+//! apply a single S-box, so most of their work lies in the dense t×t mix. Two
+//! transformations, computed once per set of parameters, reduce it:
+//!
+//! - Constant folding: in a partial round, round constants not entering the
+//!   S-box are moved through the mix into the next round. Partial rounds are
+//!   then left with a single constant.
+//! - Sparse factorization: the MDS matrix M of a partial round is factored as
+//!   M = M'' · M', where M' leaves the S-box element untouched and M'' is
+//!   sparse. M' commutes with the S-box and is merged into the mix of the
+//!   previous round, whose matrix is factored in turn. Partial rounds are then
+//!   left with a sparse mix costing 2t - 1 multiplications instead of t², the
+//!   factor M' of the first partial round being applied once beforehand.
+//!
+//! The resulting permutation is identical to [`crate::permutation::permute`].
+
+use crate::fixed::{ark, mix, sbox_full, sbox_partial};
+use crate::parameters::{
+    FromParameters, Parameters, ParametersError, PreparedParameters, ToPrepared,
+};
+use alloc::{string::String, vec::Vec};
+use ff::PrimeField;
+
+/// Sparse matrix equal to the identity except for its last row and column.
+#[derive(Clone, Debug)]
+struct SparseMatrix<GF> {
+    // Last row, including the diagonal element.
+    row: Vec<GF>,
+    // Last column, excluding the diagonal element.
+    column: Vec<GF>,
+}
+
+impl<GF> SparseMatrix<GF>
+where
+    GF: PrimeField,
+{
+    fn apply(&self, state: &mut [GF]) {
+        let (last, head) = state.split_last_mut().unwrap();
+        let mut acc = self.row[head.len()] * *last;
+        for ((x, r), c) in head.iter_mut().zip(&self.row).zip(&self.column) {
+            acc += *r * *x;
+            *x += *c * *last;
+        }
+        *last = acc;
+    }
+}
+
+/// Parameters transformed for the optimized permutation.
+#[derive(Clone, Debug)]
+pub struct OptimizedParameters<GF> {
+    power: u8,
+    width: usize,
+    n_partial_rounds: usize,
+    n_full_rounds: usize,
+    mds_matrix: Vec<GF>,
+    // Factor M' left over by the first partial round.
+    pre_sparse_matrix: Vec<GF>,
+    sparse_matrices: Vec<SparseMatrix<GF>>,
+    // Constants of full rounds, width per round.
+    full_constants: Vec<GF>,
+    // Constants of partial rounds, one per round.
+    partial_constants: Vec<GF>,
+}
+
+impl<GF> OptimizedParameters<GF>
+where
+    GF: PrimeField,
+{
+    /// Transforms parameters for the optimized permutation.
+    ///
+    /// Fails if the parameters are inconsistent, if they have no full round,
+    /// or if the MDS matrix, although invertible, has a singular top-left
+    /// submatrix in one of its powers, which cannot happen for MDS matrices.
+    pub fn new<P>(params: &P) -> Result<Self, ParametersError>
+    where
+        P: ToPrepared<GF> + ?Sized,
+    {
+        let params = params.to_prepared();
+        params.validate()?;
+        if params.n_full_rounds == 0 {
+            return Err(ParametersError::ZeroFullRounds);
+        }
+        let t = params.rate + params.capacity;
+        let rf = params.n_full_rounds / 2;
+        let rp = params.n_partial_rounds;
+        let n_rounds = params.n_full_rounds + rp;
+        let mds_matrix = params.mds_matrix.clone();
+
+        // Constant folding, from the first partial round onwards.
+        let mut full_constants = Vec::with_capacity(params.n_full_rounds * t);
+        full_constants.extend_from_slice(&params.round_constants[..rf * t]);
+        let mut partial_constants = Vec::with_capacity(rp);
+        let mut carry = vec![GF::ZERO; t];
+        let mut scratch = vec![GF::ZERO; t];
+        for i in rf..(rf + rp) {
+            let mut constants = carry;
+            for (c, rc) in constants.iter_mut().zip(&params.round_constants[i * t..]) {
+                *c += rc;
+            }
+            partial_constants.push(constants[t - 1]);
+            constants[t - 1] = GF::ZERO;
+            mix(&mut constants, &mds_matrix, &mut scratch);
+            carry = constants;
+        }
+        for (j, c) in carry.iter().enumerate() {
+            full_constants.push(params.round_constants[(rf + rp) * t + j] + c);
+        }
+        full_constants
+            .extend_from_slice(&params.round_constants[((rf + rp + 1) * t)..(n_rounds * t)]);
+
+        // Sparse factorization, from the last partial round backwards.
+        let mut sparse_matrices = Vec::with_capacity(rp);
+        let mut factor = mds_matrix.clone();
+        for i in 0..rp {
+            let acc = if i == 0 {
+                mds_matrix.clone()
+            } else {
+                mat_mul(&factor, &mds_matrix, t)
+            };
+            let sparse;
+            (sparse, factor) =
+                factor_sparse(&acc, t).ok_or(ParametersError::SingularMdsSubmatrix)?;
+            sparse_matrices.push(sparse);
+        }
+        sparse_matrices.reverse();
+
+        Ok(OptimizedParameters {
+            power: params.power,
+            width: t,
+            n_partial_rounds: rp,
+            n_full_rounds: params.n_full_rounds,
+            mds_matrix,
+            pre_sparse_matrix: factor,
+            sparse_matrices,
+            full_constants,
+            partial_constants,
+        })
+    }
+}
+
+impl<GF> FromParameters for OptimizedParameters<GF>
+where
+    GF: PrimeField,
+{
+    /// Panics if the parameters cannot be optimized, which does not happen
+    /// for the sets of parameters of the crate.
+    fn from_parameters(params: &Parameters) -> Self {
+        Self::new(&PreparedParameters::new(params))
+            .expect("Parameters of the crate can be optimized")
+    }
+}
+
+/// Computes a × b for square matrices of size n in row-major order.
+pub(crate) fn mat_mul<GF>(a: &[GF], b: &[GF], n: usize) -> Vec<GF>
+where
+    GF: PrimeField,
+{
+    let mut result = vec![GF::ZERO; n * n];
+    for i in 0..n {
+        for k in 0..n {
+            for j in 0..n {
+                result[i * n + j] += a[i * n + k] * b[k * n + j];
+            }
+        }
+    }
+    result
+}
+
+/// Inverts a square matrix of size n by Gauss-Jordan elimination.
+pub(crate) fn invert<GF>(matrix: &[GF], n: usize) -> Option<Vec<GF>>
+where
+    GF: PrimeField,
+{
+    let mut m = matrix.to_vec();
+    let mut inv = vec![GF::ZERO; n * n];
+    for i in 0..n {
+        inv[i * n + i] = GF::ONE;
+    }
+    for col in 0..n {
+        let pivot = (col..n).find(|&row| !bool::from(m[row * n + col].is_zero()))?;
+        for j in 0..n {
+            m.swap(pivot * n + j, col * n + j);
+            inv.swap(pivot * n + j, col * n + j);
+        }
+        let scale = m[col * n + col].invert().unwrap();
+        for j in 0..n {
+            m[col * n + j] *= scale;
+            inv[col * n + j] *= scale;
+        }
+        for row in 0..n {
+            let factor = m[row * n + col];
+            if row == col || bool::from(factor.is_zero()) {
+                continue;
+            }
+            for j in 0..n {
+                let (mc, ic) = (m[col * n + j], inv[col * n + j]);
+                m[row * n + j] -= factor * mc;
+                inv[row * n + j] -= factor * ic;
+            }
+        }
+    }
+    Some(inv)
+}
+
+/// Factors a matrix of size t as M = M'' · M', with M'' sparse and
+/// M' = diag(Â, 1), Â being the top-left block of M. Returns M'' and M', or
+/// None if Â is singular.
+fn factor_sparse<GF>(matrix: &[GF], t: usize) -> Option<(SparseMatrix<GF>, Vec<GF>)>
+where
+    GF: PrimeField,
+{
+    let n = t - 1;
+    let mut block = Vec::with_capacity(n * n);
+    for i in 0..n {
+        block.extend_from_slice(&matrix[i * t..(i * t + n)]);
+    }
+    // Submatrices of an MDS matrix are invertible, which is preserved by
+    // multiplying blocks together, but not for any invertible matrix.
+    let block_inv = invert(&block, n)?;
+
+    // Last row of M'' is the last row of M times diag(Â^-1, 1).
+    let mut row = vec![GF::ZERO; t];
+    for j in 0..n {
+        for k in 0..n {
+            row[j] += matrix[n * t + k] * block_inv[k * n + j];
+        }
+    }
+    row[n] = matrix[n * t + n];
+    let column = (0..n).map(|i| matrix[i * t + n]).collect();
+
+    let mut factor = vec![GF::ZERO; t * t];
+    for i in 0..n {
+        factor[i * t..(i * t + n)].copy_from_slice(&block[i * n..(i * n + n)]);
+    }
+    factor[n * t + n] = GF::ONE;
+
+    Some((SparseMatrix { row, column }, factor))
+}
+
+/// Applies the Poseidon permutation with the optimized evaluation.
+///
+/// The state must hold exactly rate + capacity elements, the capacity coming
+/// last.
+pub fn permute_optimized<GF>(
+    state: &mut [GF],
+    params: &OptimizedParameters<GF>,
+) -> Result<(), String>
+where
+    GF: PrimeField,
+{
+    let t = params.width;
+    if state.len() != t {
+        return Err(format!(
+            "State length {} must be equal to rate + capacity = {}",
+            state.len(),
+            t
+        ));
+    }
+    let rf = params.n_full_rounds / 2;
+    let mut scratch = vec![GF::ZERO; t];
+
+    for i in 0..rf {
+        ark(state, &params.full_constants, i);
+        sbox_full(state, params.power);
+        mix(state, &params.mds_matrix, &mut scratch);
+    }
+    if params.n_partial_rounds > 0 {
+        mix(state, &params.pre_sparse_matrix, &mut scratch);
+    }
+    for (constant, sparse) in params.partial_constants.iter().zip(&params.sparse_matrices) {
+        state[t - 1] += constant;
+        sbox_partial(state, params.power);
+        sparse.apply(state);
+    }
+    for i in rf..params.n_full_rounds {
+        ark(state, &params.full_constants, i);
+        sbox_full(state, params.power);
+        mix(state, &params.mds_matrix, &mut scratch);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test_optimized {
+    use super::*;
+    use crate::parameters::s128b::{prepared, GF, PARAMS};
+    use ff::Field;
+
+    #[test]
+    fn test_invert() {
+        let t = prepared().rate + prepared().capacity;
+        let mds_matrix = prepared().mds_matrix();
+        let inv = invert(mds_matrix, t).unwrap();
+        let mut identity = vec![GF::ZERO; t * t];
+        for i in 0..t {
+            identity[i * t + i] = GF::ONE;
+        }
+        assert_eq!(mat_mul(mds_matrix, &inv, t), identity);
+        assert!(invert(&[GF::ONE, GF::ONE, GF::ONE, GF::ONE], 2).is_none());
+    }
+
+    #[test]
+    fn test_factor_sparse() {
+        let t = prepared().rate + prepared().capacity;
+        let mds_matrix = prepared().mds_matrix();
+        let (sparse, factor) = factor_sparse(mds_matrix, t).unwrap();
+        // Apply M' then M'' to each basis vector, recovering the columns of M.
+        for j in 0..t {
+            let mut column = vec![GF::ZERO; t];
+            column[j] = GF::ONE;
+            mix(&mut column, &factor, &mut vec![GF::ZERO; t]);
+            sparse.apply(&mut column);
+            let expected: Vec<GF> = (0..t).map(|i| mds_matrix[i * t + j]).collect();
+            assert_eq!(column, expected);
+        }
+    }
+
+    #[test]
+    fn test_permute_optimized() {
+        let params = OptimizedParameters::new(prepared()).unwrap();
+        let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+        let mut expected = state.clone();
+        permute_optimized(&mut state, &params).unwrap();
+        crate::permutation::permute(&mut expected, prepared()).unwrap();
+        assert_eq!(state, expected);
+        assert!(permute_optimized(&mut state[..2], &params).is_err());
+    }
+
+    #[test]
+    fn test_odd_full_rounds() {
+        let params = crate::parameters::ParametersBuilder::new()
+            .power(PARAMS.power)
+            .rate(PARAMS.rate)
+            .capacity(PARAMS.capacity)
+            .output_size(PARAMS.output_size)
+            .n_full_rounds(3)
+            .n_partial_rounds(2)
+            .build_prepared(
+                prepared().mds_matrix().to_vec(),
+                (0..15).map(GF::from).collect(),
+            )
+            .unwrap();
+        let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+        let mut expected = state.clone();
+        permute_optimized(&mut state, &OptimizedParameters::new(&params).unwrap()).unwrap();
+        crate::permutation::permute(&mut expected, &params).unwrap();
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let params = crate::parameters::ParametersBuilder::new()
+            .power(PARAMS.power)
+            .rate(PARAMS.rate)
+            .capacity(PARAMS.capacity)
+            .output_size(PARAMS.output_size)
+            .n_full_rounds(0)
+            .n_partial_rounds(2)
+            .build_prepared(prepared().mds_matrix().to_vec(), vec![GF::ZERO; 6])
+            .unwrap();
+        assert_eq!(
+            OptimizedParameters::new(&params).err(),
+            Some(ParametersError::ZeroFullRounds)
+        );
+
+        let params = Parameters {
+            round_constants: &PARAMS.round_constants[1..],
+            ..PARAMS
+        };
+        assert_eq!(
+            OptimizedParameters::<GF>::new(&params).err(),
+            Some(ParametersError::RoundConstantsLength {
+                length: PARAMS.round_constants.len() - 1,
+                expected: PARAMS.round_constants.len(),
+            })
+        );
+    }
+
+    #[test]
+    fn test_singular_submatrix() {
+        // Invertible, but its top-left element is zero.
+        let params = crate::parameters::ParametersBuilder::new()
+            .power(3)
+            .rate(1)
+            .capacity(1)
+            .output_size(1)
+            .n_full_rounds(2)
+            .n_partial_rounds(1)
+            .build_prepared(
+                vec![GF::ZERO, GF::ONE, GF::ONE, GF::ZERO],
+                vec![GF::ZERO; 6],
+            )
+            .unwrap();
+        assert_eq!(
+            OptimizedParameters::new(&params).err(),
+            Some(ParametersError::SingularMdsSubmatrix)
+        );
+    }
+}
@@ -1,3 +1,84 @@
+/// Declares the static tables of a set of parameters, converted to field
+/// elements at compile time, its fixed-width permutation and a hash function
+/// into an array of its output size.
+///
+/// Must be invoked in the module deriving the field `GF`, with the decimal
+/// modulus of the field, from which its Montgomery constants are computed.
+macro_rules! static_tables {
+    ($params:ident, $modulus:literal) => {
+        pub const WIDTH: usize = $params.rate + $params.capacity;
+        pub const OUTPUT_SIZE: usize = $params.output_size;
+
+        const MONT_MODULUS: [u64; core::mem::size_of::<GF>() / 8] =
+            $crate::fields::montgomery::limbs_from_decimal($modulus);
+        const MONT_R2: [u64; MONT_MODULUS.len()] =
+            $crate::fields::montgomery::mont_r2(&MONT_MODULUS);
+        const MONT_INV: u64 = $crate::fields::montgomery::mont_inv(MONT_MODULUS[0]);
+
+        pub(crate) const fn felts_from_str_const<const N: usize>(constants: &[&str]) -> [GF; N] {
+            let mut result = [<GF as ::ff::Field>::ZERO; N];
+            let mut i = 0;
+            while i < N {
+                result[i] = GF($crate::fields::montgomery::mont_from_decimal(
+                    constants[i],
+                    &MONT_MODULUS,
+                    &MONT_R2,
+                    MONT_INV,
+                ));
+                i += 1;
+            }
+            result
+        }
+
+        pub static MDS_MATRIX: [GF; $params.mds_matrix.len()] =
+            felts_from_str_const($params.mds_matrix);
+        pub static ROUND_CONSTANTS: [GF; $params.round_constants.len()] =
+            felts_from_str_const($params.round_constants);
+        pub static PERMUTATION: $crate::fixed::PoseidonPermutation<GF, WIDTH> =
+            $crate::fixed::PoseidonPermutation::new(&$params, &MDS_MATRIX, &ROUND_CONSTANTS);
+
+        /// Hashes inputs of length a multiple of the rate, without allocating.
+        pub fn hash(inputs: &[GF]) -> Result<[GF; OUTPUT_SIZE], $crate::fixed::HashError> {
+            let mut output = [<GF as ::ff::Field>::ZERO; OUTPUT_SIZE];
+            PERMUTATION.hash(inputs, &mut output)?;
+            Ok(output)
+        }
+    };
+}
+
+/// Declares the static tables of a set of Poseidon2 parameters, as
+/// [`static_tables`] does.
+///
+/// Must be invoked where the `felts_from_str_const` of the module deriving the
+/// field `GF` is in scope.
+macro_rules! static_poseidon2_tables {
+    ($params:ident) => {
+        pub const WIDTH: usize = $params.rate + $params.capacity;
+        pub const OUTPUT_SIZE: usize = $params.output_size;
+
+        pub static EXTERNAL_MATRIX: [GF; $params.external_matrix.len()] =
+            felts_from_str_const($params.external_matrix);
+        pub static INTERNAL_DIAGONAL: [GF; $params.internal_diagonal.len()] =
+            felts_from_str_const($params.internal_diagonal);
+        pub static ROUND_CONSTANTS: [GF; $params.round_constants.len()] =
+            felts_from_str_const($params.round_constants);
+        pub static PERMUTATION: $crate::fixed::Poseidon2Permutation<GF, WIDTH> =
+            $crate::fixed::Poseidon2Permutation::new(
+                &$params,
+                &EXTERNAL_MATRIX,
+                &INTERNAL_DIAGONAL,
+                &ROUND_CONSTANTS,
+            );
+
+        /// Hashes inputs of length a multiple of the rate, without allocating.
+        pub fn hash(inputs: &[GF]) -> Result<[GF; OUTPUT_SIZE], $crate::fixed::HashError> {
+            let mut output = [<GF as ::ff::Field>::ZERO; OUTPUT_SIZE];
+            PERMUTATION.hash(inputs, &mut output)?;
+            Ok(output)
+        }
+    };
+}
+
 pub mod s128b;
 mod starkware;
 pub use starkware::sw2;
@@ -9,6 +90,36 @@ mod mina;
 pub use mina::pallas;
 pub use mina::vesta;
 
+pub mod poseidon2;
+
+#[cfg(feature = "alloc")]
+mod builder;
+#[cfg(feature = "alloc")]
+pub(crate) use builder::{gcd, is_valid_power};
+#[cfg(feature = "alloc")]
+pub use builder::{ParametersBuilder, ParametersError};
+
+#[cfg(feature = "alloc")]
+pub mod runtime;
+#[cfg(feature = "alloc")]
+pub use runtime::RuntimeParameters;
+
+#[cfg(feature = "alloc")]
+mod prepared;
+#[cfg(feature = "alloc")]
+pub use prepared::{
+    FromParameters, PreparedCell, PreparedParameters, PreparedPoseidon2Parameters, ToPrepared,
+};
+
+#[cfg(feature = "serde")]
+pub mod file;
+#[cfg(feature = "serde")]
+pub use file::ParametersFile;
+
+/// Parameters of the Poseidon permutation and sponge.
+///
+/// Full rounds are split in two halves around the partial rounds, the second
+/// half taking the extra round of an odd number of full rounds.
 pub struct Parameters {
     pub power: u8,
     pub rate: usize,
@@ -19,3 +130,22 @@ pub struct Parameters {
     pub mds_matrix: &'static [&'static str],
     pub round_constants: &'static [&'static str],
 }
+
+/// Parameters of the Poseidon2 permutation.
+///
+/// The external matrix, used in full rounds, is given as a t×t matrix. The
+/// internal matrix of partial rounds is 1 + diag(internal_diagonal), that is
+/// the all-ones matrix plus a diagonal, so only the diagonal minus one is
+/// stored. Round constants hold t elements per round; partial rounds only use
+/// their first one.
+pub struct Poseidon2Parameters {
+    pub power: u8,
+    pub rate: usize,
+    pub capacity: usize,
+    pub output_size: usize,
+    pub n_partial_rounds: usize,
+    pub n_full_rounds: usize,
+    pub external_matrix: &'static [&'static str],
+    pub internal_diagonal: &'static [&'static str],
+    pub round_constants: &'static [&'static str],
+}
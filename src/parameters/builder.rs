@@ -0,0 +1,426 @@
+use super::{Parameters, PreparedParameters, RuntimeParameters};
+use crate::convert::le_bytes;
+use crate::fields::runtime::{RuntimeElement, RuntimeField};
+use crate::optimized::invert;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use ff::PrimeField;
+
+/// Inconsistencies between the fields of [`Parameters`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParametersError {
+    ZeroRate,
+    ZeroCapacity,
+    ZeroOutputSize,
+    /// x^power is not a permutation of the field.
+    InvalidPower {
+        power: u8,
+    },
+    MdsMatrixLength {
+        length: usize,
+        expected: usize,
+    },
+    SingularMdsMatrix,
+    /// The width is lower than 2 or greater than the maximal width of the
+    /// cache, or a number of rounds does not fit the seed of the Grain LFSR
+    /// generating the constants.
+    UnsupportedShape,
+    /// A top-left submatrix of a power of the MDS matrix is singular, which
+    /// the optimized permutation cannot factor. Never happens for MDS
+    /// matrices.
+    SingularMdsSubmatrix,
+    /// The optimized permutation folds the constants of the partial rounds
+    /// into the full round following them.
+    ZeroFullRounds,
+    RoundConstantsLength {
+        length: usize,
+        expected: usize,
+    },
+    /// A constant is not the decimal representation of a field element.
+    InvalidConstant {
+        constant: String,
+    },
+    /// The modulus of a parameters file differs from the one of the field.
+    ModulusMismatch,
+}
+
+impl fmt::Display for ParametersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParametersError::ZeroRate => write!(f, "Rate must be positive"),
+            ParametersError::ZeroCapacity => write!(f, "Capacity must be positive"),
+            ParametersError::ZeroOutputSize => write!(f, "Output size must be positive"),
+            ParametersError::InvalidPower { power } => write!(
+                f,
+                "S-box power {} must be greater than 1 and coprime to p - 1",
+                power
+            ),
+            ParametersError::MdsMatrixLength { length, expected } => write!(
+                f,
+                "MDS matrix length {} must be equal to width squared = {}",
+                length, expected
+            ),
+            ParametersError::SingularMdsMatrix => write!(f, "MDS matrix must be invertible"),
+            ParametersError::UnsupportedShape => write!(
+                f,
+                "Width must be in [2, 16] and numbers of rounds lower than 1024"
+            ),
+            ParametersError::SingularMdsSubmatrix => {
+                write!(f, "MDS submatrices must be invertible")
+            }
+            ParametersError::ZeroFullRounds => write!(f, "Number of full rounds must be positive"),
+            ParametersError::RoundConstantsLength { length, expected } => write!(
+                f,
+                "Round constants length {} must be equal to rounds times width = {}",
+                length, expected
+            ),
+            ParametersError::InvalidConstant { constant } => {
+                write!(f, "Invalid field element {}", constant)
+            }
+            ParametersError::ModulusMismatch => write!(f, "Modulus differs from the field's"),
+        }
+    }
+}
+
+impl Parameters {
+    /// Checks that the parameters are consistent over the field `GF`.
+    pub fn validate<GF>(&self) -> Result<(), ParametersError>
+    where
+        GF: PrimeField,
+    {
+        PreparedParameters {
+            power: self.power,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+            mds_matrix: parse::<GF>(self.mds_matrix)?,
+            round_constants: parse::<GF>(self.round_constants)?,
+        }
+        .validate()
+    }
+}
+
+impl<GF> PreparedParameters<GF>
+where
+    GF: PrimeField,
+{
+    /// Checks that the parameters are consistent.
+    pub fn validate(&self) -> Result<(), ParametersError> {
+        if self.rate == 0 {
+            return Err(ParametersError::ZeroRate);
+        }
+        if self.capacity == 0 {
+            return Err(ParametersError::ZeroCapacity);
+        }
+        if self.output_size == 0 {
+            return Err(ParametersError::ZeroOutputSize);
+        }
+        if !is_valid_power::<GF>(self.power) {
+            return Err(ParametersError::InvalidPower { power: self.power });
+        }
+
+        let width = self.rate + self.capacity;
+        if self.mds_matrix.len() != width * width {
+            return Err(ParametersError::MdsMatrixLength {
+                length: self.mds_matrix.len(),
+                expected: width * width,
+            });
+        }
+        let n_rounds = self.n_full_rounds + self.n_partial_rounds;
+        if self.round_constants.len() != n_rounds * width {
+            return Err(ParametersError::RoundConstantsLength {
+                length: self.round_constants.len(),
+                expected: n_rounds * width,
+            });
+        }
+        if invert(&self.mds_matrix, width).is_none() {
+            return Err(ParametersError::SingularMdsMatrix);
+        }
+        Ok(())
+    }
+}
+
+fn parse<GF>(constants: &[&str]) -> Result<Vec<GF>, ParametersError>
+where
+    GF: PrimeField,
+{
+    constants
+        .iter()
+        .map(|&constant| {
+            GF::from_str_vartime(constant).ok_or_else(|| ParametersError::InvalidConstant {
+                constant: constant.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Returns (p - 1) mod m.
+fn modulus_minus_one_rem<GF>(m: u8) -> u64
+where
+    GF: PrimeField,
+{
+    le_bytes(&-GF::ONE)
+        .iter()
+        .rev()
+        .fold(0, |rem, &b| ((rem << 8) | b as u64) % m as u64)
+}
+
+/// Returns whether x^power is a permutation of the field.
+pub(crate) fn is_valid_power<GF>(power: u8) -> bool
+where
+    GF: PrimeField,
+{
+    power >= 2 && gcd(power as u64, modulus_minus_one_rem::<GF>(power)) == 1
+}
+
+pub(crate) fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Builds [`Parameters`], validated over the field `GF`.
+///
+/// ```
+/// use poseidon::parameters::{s128b, ParametersBuilder, ParametersError};
+/// let params = ParametersBuilder::new()
+///     .power(3)
+///     .rate(2)
+///     .capacity(1)
+///     .output_size(1)
+///     .n_full_rounds(8)
+///     .n_partial_rounds(83)
+///     .mds_matrix(s128b::PARAMS.mds_matrix)
+///     .round_constants(s128b::PARAMS.round_constants)
+///     .build::<s128b::GF>()
+///     .unwrap();
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ParametersBuilder {
+    power: u8,
+    rate: usize,
+    capacity: usize,
+    output_size: usize,
+    n_partial_rounds: usize,
+    n_full_rounds: usize,
+    mds_matrix: &'static [&'static str],
+    round_constants: &'static [&'static str],
+}
+
+impl ParametersBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn power(mut self, power: u8) -> Self {
+        self.power = power;
+        self
+    }
+
+    pub fn rate(mut self, rate: usize) -> Self {
+        self.rate = rate;
+        self
+    }
+
+    pub fn capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity;
+        self
+    }
+
+    pub fn output_size(mut self, output_size: usize) -> Self {
+        self.output_size = output_size;
+        self
+    }
+
+    pub fn n_partial_rounds(mut self, n_partial_rounds: usize) -> Self {
+        self.n_partial_rounds = n_partial_rounds;
+        self
+    }
+
+    pub fn n_full_rounds(mut self, n_full_rounds: usize) -> Self {
+        self.n_full_rounds = n_full_rounds;
+        self
+    }
+
+    pub fn mds_matrix(mut self, mds_matrix: &'static [&'static str]) -> Self {
+        self.mds_matrix = mds_matrix;
+        self
+    }
+
+    pub fn round_constants(mut self, round_constants: &'static [&'static str]) -> Self {
+        self.round_constants = round_constants;
+        self
+    }
+
+    pub fn build<GF>(self) -> Result<Parameters, ParametersError>
+    where
+        GF: PrimeField,
+    {
+        let params = Parameters {
+            power: self.power,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+            mds_matrix: self.mds_matrix,
+            round_constants: self.round_constants,
+        };
+        params.validate::<GF>()?;
+        Ok(params)
+    }
+
+    /// Builds prepared parameters owning the given MDS matrix and round
+    /// constants, for tables generated or loaded at runtime. The string tables
+    /// of the builder are not used.
+    pub fn build_prepared<GF>(
+        self,
+        mds_matrix: Vec<GF>,
+        round_constants: Vec<GF>,
+    ) -> Result<PreparedParameters<GF>, ParametersError>
+    where
+        GF: PrimeField,
+    {
+        let prepared = PreparedParameters {
+            power: self.power,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+            mds_matrix,
+            round_constants,
+        };
+        prepared.validate()?;
+        Ok(prepared)
+    }
+
+    /// Builds parameters over a field known at runtime, owning the given MDS
+    /// matrix and round constants. The string tables of the builder are not
+    /// used.
+    pub fn build_runtime(
+        self,
+        field: RuntimeField,
+        mds_matrix: Vec<RuntimeElement>,
+        round_constants: Vec<RuntimeElement>,
+    ) -> Result<RuntimeParameters, ParametersError> {
+        let params = RuntimeParameters {
+            field,
+            power: self.power,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+            mds_matrix,
+            round_constants,
+        };
+        params.validate()?;
+        Ok(params)
+    }
+}
+
+#[cfg(test)]
+mod test_builder {
+    use super::*;
+    use crate::convert::felts_from_str;
+    use crate::parameters::s128b::{self, GF, PARAMS};
+
+    fn builder() -> ParametersBuilder {
+        ParametersBuilder::new()
+            .power(PARAMS.power)
+            .rate(PARAMS.rate)
+            .capacity(PARAMS.capacity)
+            .output_size(PARAMS.output_size)
+            .n_full_rounds(PARAMS.n_full_rounds)
+            .n_partial_rounds(PARAMS.n_partial_rounds)
+            .mds_matrix(PARAMS.mds_matrix)
+            .round_constants(PARAMS.round_constants)
+    }
+
+    #[test]
+    fn test_build() {
+        let params = builder().build::<GF>().unwrap();
+        assert_eq!(params.round_constants, PARAMS.round_constants);
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |builder: ParametersBuilder| builder.build::<GF>().err().unwrap();
+        assert_eq!(error(builder().rate(0)), ParametersError::ZeroRate);
+        assert_eq!(error(builder().capacity(0)), ParametersError::ZeroCapacity);
+        assert_eq!(
+            error(builder().output_size(0)),
+            ParametersError::ZeroOutputSize
+        );
+        // p - 1 = 2^253 + 2^199 is not divisible by 3, but is by 2.
+        assert_eq!(
+            error(builder().power(2)),
+            ParametersError::InvalidPower { power: 2 }
+        );
+        assert_eq!(
+            error(builder().power(1)),
+            ParametersError::InvalidPower { power: 1 }
+        );
+        assert_eq!(
+            error(builder().mds_matrix(&PARAMS.mds_matrix[1..])),
+            ParametersError::MdsMatrixLength {
+                length: 8,
+                expected: 9
+            }
+        );
+        assert_eq!(
+            error(builder().mds_matrix(&["1", "1", "1", "1", "1", "1", "1", "1", "1"])),
+            ParametersError::SingularMdsMatrix
+        );
+        assert_eq!(
+            error(builder().n_partial_rounds(84)),
+            ParametersError::RoundConstantsLength {
+                length: 273,
+                expected: 276
+            }
+        );
+        assert_eq!(
+            error(builder().mds_matrix(&["1", "0", "0", "0", "1", "0", "0", "0", "x"])),
+            ParametersError::InvalidConstant {
+                constant: "x".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_prepared() {
+        let mds_matrix = felts_from_str::<GF>(PARAMS.mds_matrix);
+        let round_constants = felts_from_str::<GF>(PARAMS.round_constants);
+        let prepared = builder()
+            .build_prepared(mds_matrix.clone(), round_constants.clone())
+            .unwrap();
+        assert_eq!(prepared.mds_matrix(), s128b::prepared().mds_matrix());
+        assert_eq!(
+            prepared.round_constants(),
+            s128b::prepared().round_constants()
+        );
+        assert_eq!(
+            builder()
+                .build_prepared(mds_matrix, round_constants[3..].to_vec())
+                .err(),
+            Some(ParametersError::RoundConstantsLength {
+                length: 270,
+                expected: 273
+            })
+        );
+    }
+
+    #[test]
+    fn test_gcd() {
+        assert_eq!(gcd(12, 18), 6);
+        assert_eq!(gcd(5, 0), 5);
+        // p - 1 = 2^253 + 2^199.
+        assert_eq!(modulus_minus_one_rem::<GF>(5), 0);
+        assert_eq!(modulus_minus_one_rem::<GF>(7), 4);
+    }
+}
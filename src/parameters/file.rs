@@ -0,0 +1,238 @@
+//! Parameters files, available with the `serde` feature.
+//!
+//! A [`ParametersFile`] describes a set of parameters in any format supported
+//! by serde. Field elements are decimal strings, so that the file is readable
+//! and does not depend on the internal representation of the field. In JSON:
+//!
+//! ```json
+//! {
+//!   "modulus": "14474011154664525231415395255581126252639794253786371766033694892385558855681",
+//!   "alpha": 3,
+//!   "rate": 2,
+//!   "capacity": 1,
+//!   "output_size": 1,
+//!   "n_full_rounds": 8,
+//!   "n_partial_rounds": 83,
+//!   "mds_matrix": ["...", "..."],
+//!   "round_constants": ["...", "..."]
+//! }
+//! ```
+//!
+//! The MDS matrix holds the t² elements of the t×t matrix, row by row, and the
+//! round constants the t elements of each round, t being rate + capacity. The
+//! modulus may also be given in hexadecimal, prefixed with `0x`. Loading a file
+//! checks its modulus against the field and validates the parameters as
+//! [`Parameters::validate`] does.
+
+use super::{Parameters, ParametersError, PreparedParameters};
+use crate::convert::{decimal_from_le_bytes, le_bytes, str_from_felts};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use ff::PrimeField;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParametersFile {
+    /// Modulus of the prime field.
+    pub modulus: String,
+    /// Power of the S-box.
+    pub alpha: u8,
+    pub rate: usize,
+    pub capacity: usize,
+    pub output_size: usize,
+    pub n_full_rounds: usize,
+    pub n_partial_rounds: usize,
+    pub mds_matrix: Vec<String>,
+    pub round_constants: Vec<String>,
+}
+
+impl ParametersFile {
+    /// Describes parameters over the field `GF`, such as the sets of the crate.
+    pub fn new<GF>(params: &Parameters) -> Self
+    where
+        GF: PrimeField,
+    {
+        ParametersFile {
+            modulus: decimal_from_le_bytes(&modulus::<GF>()),
+            alpha: params.power,
+            rate: params.rate,
+            capacity: params.capacity,
+            output_size: params.output_size,
+            n_full_rounds: params.n_full_rounds,
+            n_partial_rounds: params.n_partial_rounds,
+            mds_matrix: params.mds_matrix.iter().map(|s| s.to_string()).collect(),
+            round_constants: params
+                .round_constants
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+        }
+    }
+
+    /// Describes prepared parameters over the field `GF`.
+    pub fn from_prepared<GF>(params: &PreparedParameters<GF>) -> Self
+    where
+        GF: PrimeField,
+    {
+        ParametersFile {
+            modulus: decimal_from_le_bytes(&modulus::<GF>()),
+            alpha: params.power,
+            rate: params.rate,
+            capacity: params.capacity,
+            output_size: params.output_size,
+            n_full_rounds: params.n_full_rounds,
+            n_partial_rounds: params.n_partial_rounds,
+            mds_matrix: str_from_felts(&params.mds_matrix),
+            round_constants: str_from_felts(&params.round_constants),
+        }
+    }
+
+    /// Parses the constants over the field `GF` and validates the parameters.
+    pub fn to_prepared<GF>(&self) -> Result<PreparedParameters<GF>, ParametersError>
+    where
+        GF: PrimeField,
+    {
+        let expected = modulus::<GF>();
+        if le_bytes_from_str(&self.modulus).as_deref() != Some(trim(&expected)) {
+            return Err(ParametersError::ModulusMismatch);
+        }
+        let prepared = PreparedParameters {
+            power: self.alpha,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+            mds_matrix: parse(&self.mds_matrix)?,
+            round_constants: parse(&self.round_constants)?,
+        };
+        prepared.validate()?;
+        Ok(prepared)
+    }
+}
+
+fn parse<GF>(constants: &[String]) -> Result<Vec<GF>, ParametersError>
+where
+    GF: PrimeField,
+{
+    constants
+        .iter()
+        .map(|constant| {
+            GF::from_str_vartime(constant).ok_or_else(|| ParametersError::InvalidConstant {
+                constant: constant.clone(),
+            })
+        })
+        .collect()
+}
+
+/// Returns the modulus of `GF` in little-endian bytes, as -1 + 1.
+fn modulus<GF>() -> Vec<u8>
+where
+    GF: PrimeField,
+{
+    let mut bytes = le_bytes(&-GF::ONE);
+    for byte in bytes.iter_mut() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            return bytes;
+        }
+    }
+    bytes.push(1);
+    bytes
+}
+
+/// Strips the most significant zero bytes of a little-endian integer.
+fn trim(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+/// Parses a decimal, or `0x`-prefixed hexadecimal, integer into trimmed
+/// little-endian bytes.
+fn le_bytes_from_str(s: &str) -> Option<Vec<u8>> {
+    let (digits, radix) = match s.strip_prefix("0x") {
+        Some(digits) => (digits, 16),
+        None => (s, 10),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut bytes = Vec::new();
+    for c in digits.chars() {
+        let mut carry = c.to_digit(radix)?;
+        for byte in bytes.iter_mut() {
+            let x = *byte as u32 * radix + carry;
+            *byte = x as u8;
+            carry = x >> 8;
+        }
+        if carry != 0 {
+            bytes.push(carry as u8);
+        }
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod test_file {
+    use super::*;
+    use crate::parameters::s128b::{self, GF, PARAMS};
+
+    #[test]
+    fn test_modulus() {
+        let expected =
+            "14474011154664525231415395255581126252639794253786371766033694892385558855681";
+        assert_eq!(decimal_from_le_bytes(&modulus::<GF>()), expected);
+        assert_eq!(le_bytes_from_str(expected).unwrap(), trim(&modulus::<GF>()));
+        assert_eq!(
+            le_bytes_from_str("0x2000000000000080000000000000000000000000000000000000000000000001"),
+            le_bytes_from_str(expected)
+        );
+        assert_eq!(decimal_from_le_bytes(&[]), "0");
+        assert_eq!(le_bytes_from_str("0x"), None);
+        assert_eq!(le_bytes_from_str("12a"), None);
+    }
+
+    #[test]
+    fn test_to_prepared() {
+        let file = ParametersFile::new::<GF>(&PARAMS);
+        let prepared = file.to_prepared::<GF>().unwrap();
+        assert_eq!(prepared.mds_matrix(), s128b::prepared().mds_matrix());
+        assert_eq!(
+            prepared.round_constants(),
+            s128b::prepared().round_constants()
+        );
+        assert_eq!(ParametersFile::from_prepared(&prepared), file);
+    }
+
+    #[test]
+    fn test_to_prepared_errors() {
+        let file = ParametersFile::new::<GF>(&PARAMS);
+        assert_eq!(
+            ParametersFile {
+                modulus: "7".to_string(),
+                ..file.clone()
+            }
+            .to_prepared::<GF>()
+            .err(),
+            Some(ParametersError::ModulusMismatch)
+        );
+        let mut invalid = file.clone();
+        invalid.round_constants[0] = "x1".to_string();
+        assert_eq!(
+            invalid.to_prepared::<GF>().err(),
+            Some(ParametersError::InvalidConstant {
+                constant: "x1".to_string()
+            })
+        );
+        assert_eq!(
+            ParametersFile {
+                alpha: 2,
+                ..file.clone()
+            }
+            .to_prepared::<GF>()
+            .err(),
+            Some(ParametersError::InvalidPower { power: 2 })
+        );
+    }
+}
@@ -1,4 +1,8 @@
+#[cfg(feature = "alloc")]
+use crate::optimized::OptimizedParameters;
 use crate::parameters::Parameters;
+#[cfg(feature = "alloc")]
+use crate::parameters::{PreparedCell, PreparedParameters};
 use ff::*;
 
 #[derive(PrimeField)]
@@ -249,3 +253,26 @@ pub const PARAMS: Parameters = Parameters {
         "10888828634279127981352133512429657747610298502219125571406085952954136470354",
     ],
 };
+
+static_tables!(
+    PARAMS,
+    "28948022309329048855892746252171976963363056481941560715954676764349967630337"
+);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedParameters<GF>> = PreparedCell::new(&PARAMS);
+#[cfg(feature = "alloc")]
+static OPTIMIZED: PreparedCell<OptimizedParameters<GF>> = PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedParameters<GF> {
+    PREPARED.get()
+}
+
+/// Returns the parameters of the optimized permutation, computed on first call
+/// only.
+#[cfg(feature = "alloc")]
+pub fn optimized() -> &'static OptimizedParameters<GF> {
+    OPTIMIZED.get()
+}
@@ -1,4 +1,8 @@
+#[cfg(feature = "alloc")]
+use crate::optimized::OptimizedParameters;
 use crate::parameters::Parameters;
+#[cfg(feature = "alloc")]
+use crate::parameters::{PreparedCell, PreparedParameters};
 use ff::*;
 
 #[derive(PrimeField)]
@@ -250,3 +254,26 @@ pub const PARAMS: Parameters = Parameters {
         "4484359679395800410695081358212522306960518636189521201445105538223906998486",
     ],
 };
+
+static_tables!(
+    PARAMS,
+    "28948022309329048855892746252171976963363056481941647379679742748393362948097"
+);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedParameters<GF>> = PreparedCell::new(&PARAMS);
+#[cfg(feature = "alloc")]
+static OPTIMIZED: PreparedCell<OptimizedParameters<GF>> = PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedParameters<GF> {
+    PREPARED.get()
+}
+
+/// Returns the parameters of the optimized permutation, computed on first call
+/// only.
+#[cfg(feature = "alloc")]
+pub fn optimized() -> &'static OptimizedParameters<GF> {
+    OPTIMIZED.get()
+}
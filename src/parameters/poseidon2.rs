@@ -0,0 +1,3 @@
+pub mod pallas;
+pub mod stark252;
+pub mod vesta;
@@ -0,0 +1,403 @@
+use crate::parameters::sw3::felts_from_str_const;
+pub use crate::parameters::sw3::GF;
+use crate::parameters::Poseidon2Parameters;
+#[cfg(feature = "alloc")]
+use crate::parameters::{PreparedCell, PreparedPoseidon2Parameters};
+
+/// Poseidon2 instance of width 3 over the Stark252 field of the `sw*` sets,
+/// with their S-box and round numbers. The reference implementation has no
+/// instance for this field: the matrices are those of its width 3 instances
+/// and the round constants are generated as by its parameter script, see
+/// [`crate::grain::poseidon2_round_constants`].
+pub const PARAMS: Poseidon2Parameters = Poseidon2Parameters {
+    power: 3,
+    rate: 2,
+    capacity: 1,
+    output_size: 1,
+    n_partial_rounds: 83,
+    n_full_rounds: 8,
+    external_matrix: &[
+        // row 1
+        "2", "1", "1", // row 2
+        "1", "2", "1", // row 3
+        "1", "1", "2",
+    ],
+    internal_diagonal: &["1", "1", "2"],
+    round_constants: &[
+        "810429843562751733791768047923281638938266467412152044190092270598175644682",
+        "3004656525070205851519444124320531201034841083413362445613311675010892177433",
+        "145161965478807921993368326742687134795309325354699121491112502704178770029",
+        // next round
+        "1686246927220970836968032153933148787661194627610839674913191532495194340493",
+        "3114304851709638686151083518508747646758409096143404308348349389857377927656",
+        "1985948038737222311113392500657365359040581150982539384737147570436834658318",
+        // next round
+        "2855139683155144152006516816482763711373358183281016265624578534820429243271",
+        "2424093831958684416918319087772180211280225924715303038880209469252412514454",
+        "347352619568012859490572581500833279672841648364361889389569234888201219843",
+        // next round
+        "2173138684998357228398225606839677507730425128547937522190390850232838877718",
+        "3414676807345618823267321574012422748044987594921478410537018989964376562354",
+        "2981070522533495280517480919777204273945703511464003214476135186749639152425",
+        // next round
+        "2918812616342916545433612699858804695570316010477104401986945949672118855634",
+        "0",
+        "0",
+        // next round
+        "1377549821200305721434759334364885572568438212090510670273608502152873893248",
+        "0",
+        "0",
+        // next round
+        "2842019387777057072312290467491443043022774328950879140795513810260193771277",
+        "0",
+        "0",
+        // next round
+        "3537086203416158235274005259715118645032629041406266262216268021725492327646",
+        "0",
+        "0",
+        // next round
+        "1225595574172401626518301936126421227311629212395703734921069372999775773355",
+        "0",
+        "0",
+        // next round
+        "518290623802509036099973774349887809251606323158697575888124830731350401948",
+        "0",
+        "0",
+        // next round
+        "139234827531988716503591944962997589520031180656799722554547141125257877032",
+        "0",
+        "0",
+        // next round
+        "3435769862860572374757900344966988772413938800233415719346961382358303496285",
+        "0",
+        "0",
+        // next round
+        "2360381159721029499264743576599531437732664754556600178212358672613062265611",
+        "0",
+        "0",
+        // next round
+        "3284675395602269785443269513325957040662235625396762300407430058807200234761",
+        "0",
+        "0",
+        // next round
+        "2873501589501776849855899894349804144447043646755101356919864294490840213071",
+        "0",
+        "0",
+        // next round
+        "902770762204555239510548612831838197993925506878022557707714626371808672860",
+        "0",
+        "0",
+        // next round
+        "1851452273446536749385692915684326237190890542288650047594248977950506008770",
+        "0",
+        "0",
+        // next round
+        "2872853217495442437224827310167283315816839861341834326565695732372264478107",
+        "0",
+        "0",
+        // next round
+        "2506044034627631367901171441760205868195748138737069375725945083188936108147",
+        "0",
+        "0",
+        // next round
+        "2037003412368052639575366213944860909923225065815947622680031401537663769866",
+        "0",
+        "0",
+        // next round
+        "2260539428345004618912535014409186944216503473178610021594848774218784579226",
+        "0",
+        "0",
+        // next round
+        "829279298771933544184977265501860333914116922099855413368718173284266877419",
+        "0",
+        "0",
+        // next round
+        "2353233158545821372599275539570533355473523683450726485976881516067437747971",
+        "0",
+        "0",
+        // next round
+        "889277688171005393782395482781912317477683272932486420837796190311476744052",
+        "0",
+        "0",
+        // next round
+        "26777093952130231283490392847886258577785649530307711565194115141855146136",
+        "0",
+        "0",
+        // next round
+        "442468737084299070173932465759499410279210505289274818594270150890704282100",
+        "0",
+        "0",
+        // next round
+        "597819715556756881966258421550706335095523862548558199428266833451416310143",
+        "0",
+        "0",
+        // next round
+        "2675112484650274894711998725624484943197951924595130620354985839515322911827",
+        "0",
+        "0",
+        // next round
+        "578097123856261425480742375849427278541015550305825693809461725266249067430",
+        "0",
+        "0",
+        // next round
+        "2971767944329335206253389725960473607232336196146244758644899570878940966580",
+        "0",
+        "0",
+        // next round
+        "3167080555996436271404388903507906646291754202780173442922340805233830437966",
+        "0",
+        "0",
+        // next round
+        "779555420375732883178254997203475257076014522156198281895229167863235108257",
+        "0",
+        "0",
+        // next round
+        "2291301719466753180457058606269136023803679563769611109554920589667793632907",
+        "0",
+        "0",
+        // next round
+        "1273351010209935941949590349406545206742078950808171715231143755214906285073",
+        "0",
+        "0",
+        // next round
+        "689632155425985325282982457347092257318787805667486433755022959433984538274",
+        "0",
+        "0",
+        // next round
+        "2006930296315139431212172383779121080738854396718709430596643083103203368861",
+        "0",
+        "0",
+        // next round
+        "2951437657420822344974214808605005674128700279073031643425930281236264886483",
+        "0",
+        "0",
+        // next round
+        "96189451797542638233366312661272794348575805544000320664776097884801393733",
+        "0",
+        "0",
+        // next round
+        "915565393056160928677344657624143170555026691318929322061320404864924158757",
+        "0",
+        "0",
+        // next round
+        "2929420216963929087166592143867194776652912754175663446553027670010653367753",
+        "0",
+        "0",
+        // next round
+        "1780847251419159011717041212593234940676006814587198845790755644456120383768",
+        "0",
+        "0",
+        // next round
+        "1048828658726985134610534357157637443698648603815191684089801068433747390440",
+        "0",
+        "0",
+        // next round
+        "999312285306610064080328830823943104682447041245519847998430807192909636004",
+        "0",
+        "0",
+        // next round
+        "109131554759215167814312957565294914083064869405496584467890146552727880161",
+        "0",
+        "0",
+        // next round
+        "241526072610654061368008701778706782823919676344457618030056435658650200684",
+        "0",
+        "0",
+        // next round
+        "1897367237959894010108470156472785438147500298761068190229198340831781059488",
+        "0",
+        "0",
+        // next round
+        "919375760648477269706688967181814344219820481387631339356103745165381233716",
+        "0",
+        "0",
+        // next round
+        "3134549750281788083926717729817805135963316262420148059289227632484973350277",
+        "0",
+        "0",
+        // next round
+        "3036443579198175436607895181744621745576718088419204075731393043887293110105",
+        "0",
+        "0",
+        // next round
+        "3353154939666635214295282947182392202542176075833869392796717544335155554730",
+        "0",
+        "0",
+        // next round
+        "3419299682776255246098748752499144347618351631163384810463654435812446504666",
+        "0",
+        "0",
+        // next round
+        "3326128304504400035196252202445625230169867734550478287592039646759919070099",
+        "0",
+        "0",
+        // next round
+        "3087915849192766250690064130890494556650329582324726561616390070792168898419",
+        "0",
+        "0",
+        // next round
+        "594966611824609838276895506166103596367858834253254172626096883882835866342",
+        "0",
+        "0",
+        // next round
+        "2739479918324071312650360145218152064592980265511539756442486897029520671704",
+        "0",
+        "0",
+        // next round
+        "1494517168215525839555595729460677995228503865309331822227515646282629700531",
+        "0",
+        "0",
+        // next round
+        "3528560137484006177664673311222398837136869482912934603546102503007067995258",
+        "0",
+        "0",
+        // next round
+        "3517185575935652056248711663368551790293951322968762331146922413891364460925",
+        "0",
+        "0",
+        // next round
+        "2337157636067113778014535440298248244908466552303329598838707074259339069642",
+        "0",
+        "0",
+        // next round
+        "413375794565897471727035086949403378750420123162265471873821246948137731107",
+        "0",
+        "0",
+        // next round
+        "2464657613683686581330506344199817393291696181613603398799502066337321857772",
+        "0",
+        "0",
+        // next round
+        "2325091833405774790212518639550452950870234384365076817710451678853839047823",
+        "0",
+        "0",
+        // next round
+        "2056986406195001557900307853532610382081651885732380795282942824413963943455",
+        "0",
+        "0",
+        // next round
+        "3603883970826459223338830405795209192778857790601977904306618775948884469540",
+        "0",
+        "0",
+        // next round
+        "2913526087837832568319287074268205620540075004197942203543506506479600197463",
+        "0",
+        "0",
+        // next round
+        "2030426254950474412505520746190072701174045771944909774230397022274603933563",
+        "0",
+        "0",
+        // next round
+        "1825899910831687654080844739113615168639791926566071195951787598027793454880",
+        "0",
+        "0",
+        // next round
+        "2802544752041781230204687893396446580444946453966708531270571077516008178878",
+        "0",
+        "0",
+        // next round
+        "2708018028313775980434099451447255691512221362538315326410919895836008838111",
+        "0",
+        "0",
+        // next round
+        "1128214791098739311965475411464478132477724571200310669413504686407896327502",
+        "0",
+        "0",
+        // next round
+        "758123384281314521995887020719298049748450030617144262835507958950485516681",
+        "0",
+        "0",
+        // next round
+        "2159098350511180285261361531557069806993379217001845657160233270596726363670",
+        "0",
+        "0",
+        // next round
+        "1434776132283690563453802039922920942140084105890374115044210775018892067573",
+        "0",
+        "0",
+        // next round
+        "3051354689225402006336146664682260925426588649700047318055972043809869853958",
+        "0",
+        "0",
+        // next round
+        "2966777043612518729940802637227418693586996881853023824115592195324535165161",
+        "0",
+        "0",
+        // next round
+        "1147152611674540197577660585712795758575029443667741269585556264673494074519",
+        "0",
+        "0",
+        // next round
+        "144133370373938125973395208540590069573777068409925344310253493616993447729",
+        "0",
+        "0",
+        // next round
+        "3249295213982023791027648439711878288824110248402383112697830524674768373947",
+        "0",
+        "0",
+        // next round
+        "1553626844750240146883136744380090027026419198862266769651371648619992929872",
+        "0",
+        "0",
+        // next round
+        "2808773987884071936283057533983800291449322060056872324127448179900421597018",
+        "0",
+        "0",
+        // next round
+        "173520243912405833939760729524029006214948431332940902066384548141545362049",
+        "0",
+        "0",
+        // next round
+        "3136451891333594952862247671883649051292939419981396782316258330867927956717",
+        "0",
+        "0",
+        // next round
+        "1158706485310684492652204114996888275857907540992916523581475344692495852792",
+        "0",
+        "0",
+        // next round
+        "2862387343290469113433951630807358787895854510215548933470161702014430884128",
+        "0",
+        "0",
+        // next round
+        "45516467721008535044965665733731026166447356318726189067999218812708015854",
+        "0",
+        "0",
+        // next round
+        "840554772871508482183643907436355514724477090183838459633617001993516176879",
+        "0",
+        "0",
+        // next round
+        "1960558315278012850921644643311662015751698979393696519057726103085566200226",
+        "0",
+        "0",
+        // next round
+        "2354251165963801820380189074511903283020100432210434741921677371875452063168",
+        "3156988675267405062980225409367242761167193373071190332396459483760965925567",
+        "3556238172199275953397071694186739354833625905893118483166478381070406105603",
+        // next round
+        "2419140839030374289824604059413235360278754749967638076828758401792251455969",
+        "912356143853477446569285578944752170731769203447580248108966252581286046895",
+        "2691528745854765773836139105843480920118273831564938024822022078827826149048",
+        // next round
+        "2612925238358714629558478754479248945350071711582974086577547179215247805592",
+        "930308980524592304413308650440603016236683864050022896708736661771917104347",
+        "2660396326391017342218534089946336190995895695402651019923570574217447811463",
+        // next round
+        "1877807168967959589397044192843570679672765228223003839094831606431516996330",
+        "11955962124041278246156835190840258902469564578393686554861159087646453562",
+        "3153326430875198740438867861233934243191611541825524498111861105851219318705",
+    ],
+};
+
+static_poseidon2_tables!(PARAMS);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedPoseidon2Parameters<GF>, Poseidon2Parameters> =
+    PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedPoseidon2Parameters<GF> {
+    PREPARED.get()
+}
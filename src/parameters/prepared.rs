@@ -0,0 +1,301 @@
+//! Parameters parsed into field elements, and their lazy caching in statics.
+
+use super::{Parameters, Poseidon2Parameters};
+use crate::convert::felts_from_str;
+use alloc::{borrow::Cow, boxed::Box, vec::Vec};
+use core::marker::PhantomData;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+use ff::PrimeField;
+
+/// Parameters whose MDS matrix and round constants are parsed into field
+/// elements.
+///
+/// Parsing the decimal constants dominates the cost of hashing short inputs, so
+/// prepared parameters are meant to be built once and reused. Each set of
+/// parameters of the crate caches its own, see for example
+/// [`super::s128b::prepared`].
+#[derive(Clone, Debug)]
+pub struct PreparedParameters<GF> {
+    pub(crate) power: u8,
+    pub(crate) rate: usize,
+    pub(crate) capacity: usize,
+    pub(crate) output_size: usize,
+    pub(crate) n_partial_rounds: usize,
+    pub(crate) n_full_rounds: usize,
+    pub(crate) mds_matrix: Vec<GF>,
+    pub(crate) round_constants: Vec<GF>,
+}
+
+impl<GF> PreparedParameters<GF>
+where
+    GF: PrimeField,
+{
+    pub fn new(params: &Parameters) -> Self {
+        PreparedParameters {
+            power: params.power,
+            rate: params.rate,
+            capacity: params.capacity,
+            output_size: params.output_size,
+            n_partial_rounds: params.n_partial_rounds,
+            n_full_rounds: params.n_full_rounds,
+            mds_matrix: felts_from_str(params.mds_matrix),
+            round_constants: felts_from_str(params.round_constants),
+        }
+    }
+
+    pub fn power(&self) -> u8 {
+        self.power
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    pub fn n_partial_rounds(&self) -> usize {
+        self.n_partial_rounds
+    }
+
+    pub fn n_full_rounds(&self) -> usize {
+        self.n_full_rounds
+    }
+
+    pub fn mds_matrix(&self) -> &[GF] {
+        &self.mds_matrix
+    }
+
+    pub fn round_constants(&self) -> &[GF] {
+        &self.round_constants
+    }
+}
+
+/// Parameters from which prepared parameters can be obtained.
+///
+/// Hashing functions accept either [`Parameters`], prepared on each call, or
+/// [`PreparedParameters`], borrowed as is. Poseidon2 functions likewise accept
+/// [`Poseidon2Parameters`] or [`PreparedPoseidon2Parameters`].
+pub trait ToPrepared<GF: Clone, T: Clone = PreparedParameters<GF>> {
+    fn to_prepared(&self) -> Cow<'_, T>;
+}
+
+impl<GF> FromParameters for PreparedParameters<GF>
+where
+    GF: PrimeField,
+{
+    fn from_parameters(params: &Parameters) -> Self {
+        Self::new(params)
+    }
+}
+
+impl<GF> ToPrepared<GF> for Parameters
+where
+    GF: PrimeField,
+{
+    fn to_prepared(&self) -> Cow<'_, PreparedParameters<GF>> {
+        Cow::Owned(PreparedParameters::new(self))
+    }
+}
+
+impl<GF> ToPrepared<GF> for PreparedParameters<GF>
+where
+    GF: PrimeField,
+{
+    fn to_prepared(&self) -> Cow<'_, PreparedParameters<GF>> {
+        Cow::Borrowed(self)
+    }
+}
+
+/// Poseidon2 parameters whose constants are parsed into field elements.
+#[derive(Clone, Debug)]
+pub struct PreparedPoseidon2Parameters<GF> {
+    pub(crate) power: u8,
+    pub(crate) rate: usize,
+    pub(crate) capacity: usize,
+    pub(crate) output_size: usize,
+    pub(crate) n_partial_rounds: usize,
+    pub(crate) n_full_rounds: usize,
+    pub(crate) external_matrix: Vec<GF>,
+    pub(crate) internal_diagonal: Vec<GF>,
+    pub(crate) round_constants: Vec<GF>,
+}
+
+impl<GF> PreparedPoseidon2Parameters<GF>
+where
+    GF: PrimeField,
+{
+    pub fn new(params: &Poseidon2Parameters) -> Self {
+        PreparedPoseidon2Parameters {
+            power: params.power,
+            rate: params.rate,
+            capacity: params.capacity,
+            output_size: params.output_size,
+            n_partial_rounds: params.n_partial_rounds,
+            n_full_rounds: params.n_full_rounds,
+            external_matrix: felts_from_str(params.external_matrix),
+            internal_diagonal: felts_from_str(params.internal_diagonal),
+            round_constants: felts_from_str(params.round_constants),
+        }
+    }
+
+    pub fn power(&self) -> u8 {
+        self.power
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    pub fn n_partial_rounds(&self) -> usize {
+        self.n_partial_rounds
+    }
+
+    pub fn n_full_rounds(&self) -> usize {
+        self.n_full_rounds
+    }
+
+    pub fn external_matrix(&self) -> &[GF] {
+        &self.external_matrix
+    }
+
+    pub fn internal_diagonal(&self) -> &[GF] {
+        &self.internal_diagonal
+    }
+
+    pub fn round_constants(&self) -> &[GF] {
+        &self.round_constants
+    }
+}
+
+impl<GF> FromParameters<Poseidon2Parameters> for PreparedPoseidon2Parameters<GF>
+where
+    GF: PrimeField,
+{
+    fn from_parameters(params: &Poseidon2Parameters) -> Self {
+        Self::new(params)
+    }
+}
+
+impl<GF> ToPrepared<GF, PreparedPoseidon2Parameters<GF>> for Poseidon2Parameters
+where
+    GF: PrimeField,
+{
+    fn to_prepared(&self) -> Cow<'_, PreparedPoseidon2Parameters<GF>> {
+        Cow::Owned(PreparedPoseidon2Parameters::new(self))
+    }
+}
+
+impl<GF> ToPrepared<GF, PreparedPoseidon2Parameters<GF>> for PreparedPoseidon2Parameters<GF>
+where
+    GF: PrimeField,
+{
+    fn to_prepared(&self) -> Cow<'_, PreparedPoseidon2Parameters<GF>> {
+        Cow::Borrowed(self)
+    }
+}
+
+/// Values computed from a set of parameters, such as [`PreparedParameters`].
+pub trait FromParameters<P = Parameters> {
+    fn from_parameters(params: &P) -> Self;
+}
+
+/// Lazily prepared parameters, suitable for a static.
+///
+/// Preparation happens on first access. Concurrent first accesses may each
+/// prepare the parameters, only one of them being kept.
+pub struct PreparedCell<T, P: 'static = Parameters> {
+    params: &'static P,
+    prepared: AtomicPtr<T>,
+    _marker: PhantomData<T>,
+}
+
+impl<T, P> PreparedCell<T, P> {
+    pub const fn new(params: &'static P) -> Self {
+        PreparedCell {
+            params,
+            prepared: AtomicPtr::new(ptr::null_mut()),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T, P> PreparedCell<T, P>
+where
+    T: FromParameters<P>,
+{
+    pub fn get(&self) -> &T {
+        let mut prepared = self.prepared.load(Ordering::Acquire);
+        if prepared.is_null() {
+            let new = Box::into_raw(Box::new(T::from_parameters(self.params)));
+            prepared = match self.prepared.compare_exchange(
+                ptr::null_mut(),
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => new,
+                Err(current) => {
+                    // SAFETY: `new` was never shared.
+                    drop(unsafe { Box::from_raw(new) });
+                    current
+                }
+            };
+        }
+        // SAFETY: once set, the pointer is never changed nor freed until drop.
+        unsafe { &*prepared }
+    }
+}
+
+impl<T, P> Drop for PreparedCell<T, P> {
+    fn drop(&mut self) {
+        let prepared = *self.prepared.get_mut();
+        if !prepared.is_null() {
+            // SAFETY: the pointer comes from `Box::into_raw` in `get`.
+            drop(unsafe { Box::from_raw(prepared) });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test_parameters {
+    use super::*;
+    use crate::parameters::s128b::{self, GF, PARAMS};
+
+    #[test]
+    fn test_prepared() {
+        let prepared = PreparedParameters::<GF>::new(&PARAMS);
+        assert_eq!(prepared.rate(), PARAMS.rate);
+        assert_eq!(
+            prepared.mds_matrix()[..],
+            felts_from_str::<GF>(PARAMS.mds_matrix)[..]
+        );
+        assert_eq!(
+            prepared.round_constants()[..],
+            felts_from_str::<GF>(PARAMS.round_constants)[..]
+        );
+    }
+
+    #[test]
+    fn test_prepared_cell() {
+        let cell = PreparedCell::<PreparedParameters<GF>>::new(&PARAMS);
+        let first: *const _ = cell.get();
+        let second: *const _ = cell.get();
+        assert_eq!(first, second);
+        assert_eq!(cell.get().mds_matrix(), s128b::prepared().mds_matrix());
+    }
+}
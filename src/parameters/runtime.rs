@@ -0,0 +1,267 @@
+//! Parameters over a field whose modulus is only known at runtime.
+//!
+//! [`RuntimeParameters`] hold their tables as elements of a [`RuntimeField`]
+//! and hash as [`crate::hash`] does, for callers receiving the modulus along
+//! with the parameters, see [`crate::precompile::decode_parameters`].
+
+use super::{gcd, ParametersError};
+use crate::fields::runtime::{RuntimeElement, RuntimeField};
+use crate::fixed::HashError;
+use alloc::vec::Vec;
+
+#[derive(Clone, Debug)]
+pub struct RuntimeParameters {
+    pub(crate) field: RuntimeField,
+    pub(crate) power: u8,
+    pub(crate) rate: usize,
+    pub(crate) capacity: usize,
+    pub(crate) output_size: usize,
+    pub(crate) n_partial_rounds: usize,
+    pub(crate) n_full_rounds: usize,
+    pub(crate) mds_matrix: Vec<RuntimeElement>,
+    pub(crate) round_constants: Vec<RuntimeElement>,
+}
+
+impl RuntimeParameters {
+    pub fn field(&self) -> &RuntimeField {
+        &self.field
+    }
+
+    pub fn power(&self) -> u8 {
+        self.power
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn output_size(&self) -> usize {
+        self.output_size
+    }
+
+    pub fn n_partial_rounds(&self) -> usize {
+        self.n_partial_rounds
+    }
+
+    pub fn n_full_rounds(&self) -> usize {
+        self.n_full_rounds
+    }
+
+    pub fn mds_matrix(&self) -> &[RuntimeElement] {
+        &self.mds_matrix
+    }
+
+    pub fn round_constants(&self) -> &[RuntimeElement] {
+        &self.round_constants
+    }
+
+    /// Checks that the parameters are consistent, as
+    /// [`super::PreparedParameters::validate`] does.
+    pub fn validate(&self) -> Result<(), ParametersError> {
+        if self.rate == 0 {
+            return Err(ParametersError::ZeroRate);
+        }
+        if self.capacity == 0 {
+            return Err(ParametersError::ZeroCapacity);
+        }
+        if self.output_size == 0 {
+            return Err(ParametersError::ZeroOutputSize);
+        }
+        if self.power < 2
+            || gcd(
+                self.power as u64,
+                modulus_minus_one_rem(&self.field, self.power),
+            ) != 1
+        {
+            return Err(ParametersError::InvalidPower { power: self.power });
+        }
+
+        let width = self.rate + self.capacity;
+        if self.mds_matrix.len() != width * width {
+            return Err(ParametersError::MdsMatrixLength {
+                length: self.mds_matrix.len(),
+                expected: width * width,
+            });
+        }
+        let n_rounds = self.n_full_rounds + self.n_partial_rounds;
+        if self.round_constants.len() != n_rounds * width {
+            return Err(ParametersError::RoundConstantsLength {
+                length: self.round_constants.len(),
+                expected: n_rounds * width,
+            });
+        }
+        if !is_invertible(&self.field, &self.mds_matrix, width) {
+            return Err(ParametersError::SingularMdsMatrix);
+        }
+        Ok(())
+    }
+
+    /// Applies the permutation to a state of rate + capacity elements.
+    ///
+    /// Panics if the state has another length.
+    pub fn permute(&self, state: &mut [RuntimeElement]) {
+        let field = &self.field;
+        let width = self.rate + self.capacity;
+        assert_eq!(state.len(), width, "State length must be rate + capacity");
+        let rf = self.n_full_rounds / 2;
+        let rp = self.n_partial_rounds;
+        let mut new_state = vec![field.zero(); width];
+        for round in 0..(self.n_full_rounds + rp) {
+            let constants = &self.round_constants[round * width..(round + 1) * width];
+            for (x, c) in state.iter_mut().zip(constants) {
+                *x = field.add(x, c);
+            }
+            if round < rf || round >= rf + rp {
+                for x in state.iter_mut() {
+                    *x = field.pow(x, self.power as u64);
+                }
+            } else {
+                let last = &mut state[width - 1];
+                *last = field.pow(last, self.power as u64);
+            }
+            for (new, row) in new_state
+                .iter_mut()
+                .zip(self.mds_matrix.chunks_exact(width))
+            {
+                *new = field.zero();
+                for (mij, x) in row.iter().zip(state.iter()) {
+                    *new = field.add(new, &field.mul(mij, x));
+                }
+            }
+            state.copy_from_slice(&new_state);
+        }
+    }
+
+    /// Hashes inputs whose length is a positive multiple of the rate.
+    pub fn hash(&self, inputs: &[RuntimeElement]) -> Result<Vec<RuntimeElement>, HashError> {
+        if inputs.is_empty() {
+            return Err(HashError::EmptyInputs);
+        }
+        if !inputs.len().is_multiple_of(self.rate) {
+            return Err(HashError::InputLength {
+                length: inputs.len(),
+                rate: self.rate,
+            });
+        }
+        let field = &self.field;
+        let mut state = vec![field.zero(); self.rate + self.capacity];
+        for block in inputs.chunks_exact(self.rate) {
+            for (x, input) in state.iter_mut().zip(block) {
+                *x = field.add(x, input);
+            }
+            self.permute(&mut state);
+        }
+        let mut output = Vec::with_capacity(self.output_size);
+        for i in 0..self.output_size {
+            if i > 0 && i % self.rate == 0 {
+                self.permute(&mut state);
+            }
+            output.push(state[i % self.rate]);
+        }
+        Ok(output)
+    }
+}
+
+/// Returns (p - 1) mod m, p being odd.
+pub(crate) fn modulus_minus_one_rem(field: &RuntimeField, m: u8) -> u64 {
+    let mut limbs = *field.modulus();
+    limbs[0] -= 1;
+    limbs.iter().rev().fold(0, |rem, &limb| {
+        ((((rem as u128) << 64) | limb as u128) % m as u128) as u64
+    })
+}
+
+/// Returns whether a matrix of size n is invertible, by Gaussian elimination.
+fn is_invertible(field: &RuntimeField, matrix: &[RuntimeElement], n: usize) -> bool {
+    let mut m = matrix.to_vec();
+    for col in 0..n {
+        let Some(pivot) = (col..n).find(|&row| m[row * n + col] != field.zero()) else {
+            return false;
+        };
+        for j in 0..n {
+            m.swap(pivot * n + j, col * n + j);
+        }
+        let Some(scale) = field.invert(&m[col * n + col]) else {
+            return false;
+        };
+        for row in col + 1..n {
+            let factor = field.mul(&m[row * n + col], &scale);
+            for j in col..n {
+                let x = field.mul(&factor, &m[col * n + j]);
+                m[row * n + j] = field.sub(&m[row * n + j], &x);
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod test_runtime {
+    use super::*;
+    use crate::fields::runtime::BYTES;
+    use crate::parameters::ParametersBuilder;
+
+    fn field() -> RuntimeField {
+        // p = 2^64 + 13.
+        let mut modulus = [0u8; BYTES];
+        modulus[BYTES - 9] = 1;
+        modulus[BYTES - 1] = 13;
+        RuntimeField::from_be_bytes(&modulus).unwrap()
+    }
+
+    fn element(field: &RuntimeField, x: u64) -> RuntimeElement {
+        let mut bytes = [0u8; BYTES];
+        bytes[BYTES - 8..].copy_from_slice(&x.to_be_bytes());
+        field.element_from_be_bytes(&bytes).unwrap()
+    }
+
+    fn build(mds_matrix: &[u64]) -> Result<RuntimeParameters, ParametersError> {
+        let field = field();
+        let mds_matrix = mds_matrix.iter().map(|&x| element(&field, x)).collect();
+        let round_constants = (0..4).map(|x| element(&field, x)).collect();
+        ParametersBuilder::new()
+            .power(5)
+            .rate(1)
+            .capacity(1)
+            .output_size(2)
+            .n_full_rounds(2)
+            .build_runtime(field, mds_matrix, round_constants)
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(build(&[1, 0, 0, 1]).is_ok());
+        assert!(build(&[0, 1, 1, 0]).is_ok());
+        assert_eq!(
+            build(&[1, 2, 2, 4]).err(),
+            Some(ParametersError::SingularMdsMatrix)
+        );
+        assert_eq!(
+            build(&[1, 0, 0]).err(),
+            Some(ParametersError::MdsMatrixLength {
+                length: 3,
+                expected: 4
+            })
+        );
+    }
+
+    #[test]
+    fn test_hash() {
+        // Two full rounds of width 2 with the identity matrix and constants
+        // (0, 1) and (2, 3), as in the precompile tests.
+        let params = build(&[1, 0, 0, 1]).unwrap();
+        let field = params.field();
+        let output = params.hash(&[element(field, 4)]).unwrap();
+        let x = (4u128.pow(5) + 2).pow(5);
+        assert_eq!(output[0], element(field, x as u64));
+        let mut state = vec![element(field, 4), field.zero()];
+        params.permute(&mut state);
+        params.permute(&mut state);
+        assert_eq!(output[1], state[0]);
+        assert_eq!(params.hash(&[]), Err(HashError::EmptyInputs));
+    }
+}
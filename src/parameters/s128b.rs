@@ -1,4 +1,8 @@
 use super::Parameters;
+#[cfg(feature = "alloc")]
+use super::{PreparedCell, PreparedParameters};
+#[cfg(feature = "alloc")]
+use crate::optimized::OptimizedParameters;
 use ff::*;
 
 #[derive(PrimeField)]
@@ -394,3 +398,26 @@ pub const PARAMS: Parameters = Parameters {
         "13625519590726389313330826648331135196773683203806269224524730504117898644586",
     ],
 };
+
+static_tables!(
+    PARAMS,
+    "14474011154664525231415395255581126252639794253786371766033694892385558855681"
+);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedParameters<GF>> = PreparedCell::new(&PARAMS);
+#[cfg(feature = "alloc")]
+static OPTIMIZED: PreparedCell<OptimizedParameters<GF>> = PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedParameters<GF> {
+    PREPARED.get()
+}
+
+/// Returns the parameters of the optimized permutation, computed on first call
+/// only.
+#[cfg(feature = "alloc")]
+pub fn optimized() -> &'static OptimizedParameters<GF> {
+    OPTIMIZED.get()
+}
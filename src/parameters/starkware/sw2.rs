@@ -1,4 +1,8 @@
+#[cfg(feature = "alloc")]
+use crate::optimized::OptimizedParameters;
 use crate::parameters::Parameters;
+#[cfg(feature = "alloc")]
+use crate::parameters::{PreparedCell, PreparedParameters};
 use ff::*;
 
 #[derive(PrimeField)]
@@ -394,3 +398,26 @@ pub const PARAMS: Parameters = Parameters {
         "2770011224727997178743274791849308200493823127651418989170761007078565678171",
     ],
 };
+
+static_tables!(
+    PARAMS,
+    "3618502788666131213697322783095070105623107215331596699973092056135872020481"
+);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedParameters<GF>> = PreparedCell::new(&PARAMS);
+#[cfg(feature = "alloc")]
+static OPTIMIZED: PreparedCell<OptimizedParameters<GF>> = PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedParameters<GF> {
+    PREPARED.get()
+}
+
+/// Returns the parameters of the optimized permutation, computed on first call
+/// only.
+#[cfg(feature = "alloc")]
+pub fn optimized() -> &'static OptimizedParameters<GF> {
+    OPTIMIZED.get()
+}
@@ -1,4 +1,8 @@
+#[cfg(feature = "alloc")]
+use crate::optimized::OptimizedParameters;
 use crate::parameters::Parameters;
+#[cfg(feature = "alloc")]
+use crate::parameters::{PreparedCell, PreparedParameters};
 use ff::*;
 
 #[derive(PrimeField)]
@@ -498,3 +502,26 @@ pub const PARAMS: Parameters = Parameters {
         "2662600899665871010006649609856695263727220473364611552472965243032255906029",
     ],
 };
+
+static_tables!(
+    PARAMS,
+    "3618502788666131213697322783095070105623107215331596699973092056135872020481"
+);
+
+#[cfg(feature = "alloc")]
+static PREPARED: PreparedCell<PreparedParameters<GF>> = PreparedCell::new(&PARAMS);
+#[cfg(feature = "alloc")]
+static OPTIMIZED: PreparedCell<OptimizedParameters<GF>> = PreparedCell::new(&PARAMS);
+
+/// Returns the prepared parameters, parsed on first call only.
+#[cfg(feature = "alloc")]
+pub fn prepared() -> &'static PreparedParameters<GF> {
+    PREPARED.get()
+}
+
+/// Returns the parameters of the optimized permutation, computed on first call
+/// only.
+#[cfg(feature = "alloc")]
+pub fn optimized() -> &'static OptimizedParameters<GF> {
+    OPTIMIZED.get()
+}
@@ -1,117 +1,167 @@
-use crate::convert::felts_from_str;
-use crate::parameters::Parameters;
+use crate::fixed::permute_rounds;
+use crate::parameters::{PreparedParameters, ToPrepared};
 use alloc::{
+    borrow::Cow,
     string::{String, ToString},
     vec::Vec,
 };
 use ff::PrimeField;
 
-pub struct Poseidon<'a, GF> {
-    params: &'a Parameters,
-    mds_matrix: Vec<GF>,
-    round_constants: Vec<GF>,
+/// Phase of a sponge.
+///
+/// A sponge starts absorbing. Switching phase always permutes the state, which
+/// allows interleaving absorptions and squeezes (duplex usage).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongeMode {
+    Absorbing,
+    Squeezing,
+}
+
+#[derive(Clone)]
+pub struct Poseidon<'a, GF: Clone> {
+    params: Cow<'a, PreparedParameters<GF>>,
+    mode: SpongeMode,
     offset: usize,
     state: Vec<GF>,
+    // Buffer of the mixes, so that permuting does not allocate.
+    scratch: Vec<GF>,
 }
 
 impl<'a, GF> Poseidon<'a, GF>
 where
     GF: PrimeField,
 {
-    pub fn new(params: &'a Parameters) -> Self {
+    pub fn new<P>(params: &'a P) -> Self
+    where
+        P: ToPrepared<GF> + ?Sized,
+    {
+        let params = params.to_prepared();
+        let width = params.rate + params.capacity;
         Poseidon {
-            params: params,
-            mds_matrix: felts_from_str(params.mds_matrix),
-            round_constants: felts_from_str(params.round_constants),
+            params,
+            mode: SpongeMode::Absorbing,
             offset: 0,
-            state: vec![GF::ZERO; params.rate + params.capacity],
-        }
-    }
-
-    fn ark(&mut self, round: usize) {
-        let size = self.state.len();
-        for i in 0..size {
-            self.state[i].add_assign(&self.round_constants[round * size + i]);
-        }
-    }
-
-    fn sbox_full(&mut self) {
-        for j in 0..self.state.len() {
-            let aux = self.state[j].clone();
-            for _ in 1..self.params.power {
-                self.state[j].mul_assign(aux);
-            }
+            state: vec![GF::ZERO; width],
+            scratch: vec![GF::ZERO; width],
         }
     }
 
-    fn sbox_partial(&mut self) {
-        let i = self.state.len() - 1;
-        let aux = self.state[i].clone();
-        for _ in 1..self.params.power {
-            self.state[i].mul_assign(&aux);
+    /// Creates a sponge owning its parameters, which may have been built at
+    /// runtime with [`crate::parameters::ParametersBuilder::build_prepared`].
+    pub fn from_prepared(params: PreparedParameters<GF>) -> Self {
+        let width = params.rate + params.capacity;
+        Poseidon {
+            params: Cow::Owned(params),
+            mode: SpongeMode::Absorbing,
+            offset: 0,
+            state: vec![GF::ZERO; width],
+            scratch: vec![GF::ZERO; width],
         }
     }
 
-    fn mix(&mut self) {
-        let size = self.state.len();
-        let mut new_state = vec![GF::ZERO; size];
-        for i in 0..size {
-            for j in 0..size {
-                let mut mij = self.mds_matrix[i * size + j];
-                mij.mul_assign(&self.state[j]);
-                new_state[i].add_assign(&mij);
-            }
-        }
-        self.state.clone_from(&new_state);
+    /// Creates a sponge whose first capacity element is set to `iv`.
+    ///
+    /// The capacity is never touched by inputs, so distinct IVs separate
+    /// the hashing domains.
+    pub fn new_with_iv<P>(params: &'a P, iv: GF) -> Self
+    where
+        P: ToPrepared<GF> + ?Sized,
+    {
+        let mut poseidon = Self::new(params);
+        let rate = poseidon.params.rate;
+        poseidon.state[rate] = iv;
+        poseidon
     }
 
     fn permute(&mut self) {
-        let rf = self.params.n_full_rounds / 2;
-        let rp = self.params.n_partial_rounds;
+        permute_state(&mut self.state, &self.params, &mut self.scratch);
+    }
 
-        for i in 0..rf {
-            self.ark(i);
-            self.sbox_full();
-            self.mix();
-        }
-        for i in rf..(rf + rp) {
-            self.ark(i);
-            self.sbox_partial();
-            self.mix();
-        }
-        for i in (rf + rp)..(2 * rf + rp) {
-            self.ark(i);
-            self.sbox_full();
-            self.mix();
-        }
+    /// Returns the current phase of the sponge.
+    pub fn mode(&self) -> SpongeMode {
+        self.mode
     }
 
-    fn ensure_permuted(&mut self) {
-        // offset should be <= rate, so really testing for equality
-        if self.offset >= self.params.rate {
-            self.permute();
-            self.offset = 0;
-        }
+    /// Permutes the state and starts a new phase at the beginning of the rate.
+    fn start_phase(&mut self, mode: SpongeMode) {
+        self.permute();
+        self.offset = 0;
+        self.mode = mode;
     }
 
+    /// Adds an element to the outer state.
+    ///
+    /// The state is permuted beforehand if the rate is full or if the sponge
+    /// was squeezing, so that inputs never land on already squeezed outputs.
     pub fn absorb(&mut self, input: &GF) {
-        self.ensure_permuted();
+        if self.mode == SpongeMode::Squeezing || self.offset == self.params.rate {
+            self.start_phase(SpongeMode::Absorbing);
+        }
         self.state[self.offset].add_assign(input);
         self.offset += 1;
     }
 
+    /// Reads an element from the outer state.
+    ///
+    /// The state is permuted beforehand if the sponge was absorbing or if the
+    /// rate has been entirely squeezed, so that outputs always come from a
+    /// permuted state.
     pub fn squeeze(&mut self) -> GF {
-        self.ensure_permuted();
+        if self.mode == SpongeMode::Absorbing || self.offset == self.params.rate {
+            self.start_phase(SpongeMode::Squeezing);
+        }
         let result = self.state[self.offset];
         self.offset += 1;
         result
     }
 }
 
-pub fn hash<'a, GF>(inputs: &'a [GF], params: &'a Parameters) -> Result<Vec<GF>, String>
+pub(crate) fn permute_state<GF>(
+    state: &mut [GF],
+    params: &PreparedParameters<GF>,
+    scratch: &mut [GF],
+) where
+    GF: PrimeField,
+{
+    permute_rounds(
+        state,
+        scratch,
+        params.power,
+        params.n_full_rounds,
+        params.n_partial_rounds,
+        &params.mds_matrix,
+        &params.round_constants,
+    );
+}
+
+/// Applies the Poseidon permutation to a caller-owned state.
+///
+/// The state must hold exactly rate + capacity elements, the capacity coming
+/// last.
+pub fn permute<GF, P>(state: &mut [GF], params: &P) -> Result<(), String>
+where
+    GF: PrimeField,
+    P: ToPrepared<GF> + ?Sized,
+{
+    let params = params.to_prepared();
+    let width = params.rate + params.capacity;
+    if state.len() != width {
+        return Err(format!(
+            "State length {} must be equal to rate + capacity = {}",
+            state.len(),
+            width
+        ));
+    }
+    permute_state(state, &params, &mut vec![GF::ZERO; width]);
+    Ok(())
+}
+
+pub fn hash<'a, GF, P>(inputs: &'a [GF], params: &'a P) -> Result<Vec<GF>, String>
 where
     GF: PrimeField,
+    P: ToPrepared<GF> + ?Sized,
 {
+    let params = params.to_prepared();
     if inputs.len() == 0 {
         return Err("Empty inputs".to_string());
     }
@@ -125,7 +175,7 @@ where
         .to_string());
     }
 
-    let mut poseidon = Poseidon::<'a, GF>::new(&params);
+    let mut poseidon = Poseidon::<GF>::new(&*params);
     for input in inputs {
         poseidon.absorb(input);
     }
@@ -136,10 +186,90 @@ where
     Ok(result)
 }
 
+/// Hashes consecutive messages of `message_len` elements each, returning
+/// their hash values one after the other.
+///
+/// The parameters are prepared once for all the messages, which are hashed in
+/// parallel with the `parallel` feature. `message_len` must be a positive
+/// multiple of the rate, and divide the length of `inputs`.
+pub fn hash_batch<GF, P>(inputs: &[GF], message_len: usize, params: &P) -> Result<Vec<GF>, String>
+where
+    GF: PrimeField,
+    P: ToPrepared<GF> + ?Sized,
+{
+    let params = params.to_prepared();
+    if message_len == 0 || !message_len.is_multiple_of(params.rate) {
+        return Err(format!(
+            "Message length {} must be a positive multiple of the hash rate {}",
+            message_len, params.rate
+        ));
+    }
+    if !inputs.len().is_multiple_of(message_len) {
+        return Err(format!(
+            "Input length {} must be a multiple of the message length {}",
+            inputs.len(),
+            message_len
+        ));
+    }
+    Ok(map_messages(inputs, message_len, |message| {
+        hash(message, &*params).unwrap()
+    }))
+}
+
+/// Applies `hash` to each message of `message_len` elements, concatenating
+/// the results.
+pub(crate) fn map_messages<GF, F>(inputs: &[GF], message_len: usize, hash: F) -> Vec<GF>
+where
+    GF: PrimeField,
+    F: Fn(&[GF]) -> Vec<GF> + Send + Sync,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        inputs
+            .par_chunks(message_len)
+            .map(hash)
+            .collect::<Vec<_>>()
+            .concat()
+    }
+    #[cfg(not(feature = "parallel"))]
+    inputs.chunks(message_len).flat_map(hash).collect()
+}
+
+/// Hashes a message of arbitrary length, including the empty message.
+///
+/// Follows the variable-input-length mode of the Poseidon paper (section 4.2):
+/// the message is padded with a single one followed by as many zeros as needed
+/// to reach a multiple of the rate, and the capacity is initialized to
+/// 2^64 + (o - 1), o being the output size. The padding is injective, and the
+/// IV separates this mode from the fixed-length [`hash`].
+pub fn hash_var_len<'a, GF, P>(inputs: &'a [GF], params: &'a P) -> Vec<GF>
+where
+    GF: PrimeField,
+    P: ToPrepared<GF> + ?Sized,
+{
+    let params = params.to_prepared();
+    let padded_len = (inputs.len() / params.rate + 1) * params.rate;
+    let mut padded = Vec::with_capacity(padded_len);
+    padded.extend_from_slice(inputs);
+    padded.push(GF::ONE);
+    padded.resize(padded_len, GF::ZERO);
+
+    let iv = GF::from(1u64 << 32).square() + GF::from(params.output_size as u64 - 1);
+    let mut poseidon = Poseidon::<GF>::new_with_iv(&*params, iv);
+    for input in &padded {
+        poseidon.absorb(input);
+    }
+    (0..params.output_size)
+        .map(|_| poseidon.squeeze())
+        .collect()
+}
+
 #[cfg(test)]
 mod test_permutation {
     use super::*;
     use crate::convert::felts_from_str;
+    use crate::fixed::{ark, mix, sbox_full, sbox_partial};
     use crate::parameters::s128b::{GF, PARAMS};
 
     #[test]
@@ -147,7 +277,7 @@ mod test_permutation {
         let mut poseidon = Poseidon::<GF>::new(&PARAMS);
         let input = vec![GF::from(7), GF::from(98), GF::from(0)];
         poseidon.state.clone_from(&input);
-        poseidon.ark(0);
+        ark(&mut poseidon.state, &poseidon.params.round_constants, 0);
         let expected = [
             "10187801339791605336251748402605479409606566396373491958667041943798551150218",
             "8824452141556477327634835943439996420519135454314677708228322513850226510123",
@@ -166,7 +296,7 @@ mod test_permutation {
             "5264468709835621148349527988912247104353814123939106227116596276180070073104",
         ];
         poseidon.state.clone_from(&felts_from_str::<GF>(&state));
-        poseidon.sbox_full();
+        sbox_full(&mut poseidon.state, PARAMS.power);
         let expected = [
             "9033127700447853090229678702028773675793347128105171639302548972716183808266",
             "12584005788907507820847858681541330081079761745009746063606627523756483557914",
@@ -185,7 +315,7 @@ mod test_permutation {
             "5264468709835621148349527988912247104353814123939106227116596276180070073104",
         ];
         poseidon.state.clone_from(&felts_from_str::<GF>(&state));
-        poseidon.sbox_partial();
+        sbox_partial(&mut poseidon.state, PARAMS.power);
         let expected = [
             "10187801339791605336251748402605479409606566396373491958667041943798551150218",
             "8824452141556477327634835943439996420519135454314677708228322513850226510123",
@@ -204,7 +334,11 @@ mod test_permutation {
             "481502024243180892202073663390242313819723218601880119953632240938269076973",
         ];
         poseidon.state.clone_from(&felts_from_str::<GF>(&state));
-        poseidon.mix();
+        mix(
+            &mut poseidon.state,
+            &poseidon.params.mds_matrix,
+            &mut poseidon.scratch,
+        );
         let expected = [
             "13203118710027330771388479782000018409212326464085107030609226142628639238173",
             "5548559894030014093638382051049588462182080648170927883872275099718526588448",
@@ -228,4 +362,128 @@ mod test_permutation {
         let expected = felts_from_str::<GF>(&expected);
         assert_eq!(poseidon.state, expected);
     }
+
+    #[test]
+    fn test_permute() {
+        let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+        permute(&mut state, &PARAMS).unwrap();
+        let expected = [
+            "11053447091811250430558990262025664436943237361628909971717799705027922243051",
+            "13019049864140962728034369799908465523605511214062523575537207652022843673387",
+            "1746872083923296042231738669780413133264403148587998064386629453972513813613",
+        ];
+        let expected = felts_from_str::<GF>(&expected);
+        assert_eq!(state, expected);
+    }
+
+    #[test]
+    fn test_permute_wrong_width() {
+        let mut state = vec![GF::from(7), GF::from(98)];
+        assert!(permute(&mut state, &PARAMS).is_err());
+        let mut state = vec![GF::from(0); 4];
+        assert!(permute(&mut state, &PARAMS).is_err());
+    }
+
+    #[test]
+    fn test_hash_var_len_padding() {
+        let short = hash_var_len(&[GF::from(7)], &PARAMS);
+        let zero_padded = hash_var_len(&[GF::from(7), GF::from(0)], &PARAMS);
+        let one_padded = hash_var_len(&[GF::from(7), GF::from(1)], &PARAMS);
+        assert_ne!(short, zero_padded);
+        assert_ne!(short, one_padded);
+        assert_ne!(zero_padded, one_padded);
+        assert_eq!(short.len(), PARAMS.output_size);
+    }
+
+    #[test]
+    fn test_hash_var_len_separated_from_hash() {
+        let input = [GF::from(7), GF::from(98)];
+        assert_ne!(
+            hash_var_len(&input, &PARAMS),
+            hash(&input, &PARAMS).unwrap()
+        );
+        assert_ne!(
+            hash_var_len::<GF, _>(&[], &PARAMS),
+            hash_var_len(&[GF::from(0)], &PARAMS)
+        );
+    }
+
+    #[test]
+    fn test_squeeze_partial_absorb() {
+        let mut poseidon = Poseidon::<GF>::new(&PARAMS);
+        poseidon.absorb(&GF::from(7));
+        poseidon.absorb(&GF::from(98));
+        assert_eq!(poseidon.mode(), SpongeMode::Absorbing);
+        let expected = felts_from_str::<GF>(&[
+            "11053447091811250430558990262025664436943237361628909971717799705027922243051",
+            "13019049864140962728034369799908465523605511214062523575537207652022843673387",
+        ]);
+        assert_eq!(poseidon.squeeze(), expected[0]);
+        assert_eq!(poseidon.mode(), SpongeMode::Squeezing);
+        assert_eq!(poseidon.squeeze(), expected[1]);
+
+        let mut poseidon = Poseidon::<GF>::new(&PARAMS);
+        poseidon.absorb(&GF::from(7));
+        let mut expected = Poseidon::<GF>::new(&PARAMS);
+        expected
+            .state
+            .clone_from(&vec![GF::from(7), GF::from(0), GF::from(0)]);
+        expected.permute();
+        assert_eq!(poseidon.squeeze(), expected.state[0]);
+    }
+
+    #[test]
+    fn test_squeeze_empty() {
+        let mut poseidon = Poseidon::<GF>::new(&PARAMS);
+        let mut expected = Poseidon::<GF>::new(&PARAMS);
+        expected.permute();
+        assert_eq!(poseidon.squeeze(), expected.state[0]);
+    }
+
+    #[test]
+    fn test_duplex() {
+        let mut poseidon = Poseidon::<GF>::new(&PARAMS);
+        poseidon.absorb(&GF::from(7));
+        let first = poseidon.squeeze();
+        poseidon.absorb(&GF::from(98));
+        assert_eq!(poseidon.mode(), SpongeMode::Absorbing);
+        let second = poseidon.squeeze();
+
+        let mut expected = Poseidon::<GF>::new(&PARAMS);
+        expected
+            .state
+            .clone_from(&vec![GF::from(7), GF::from(0), GF::from(0)]);
+        expected.permute();
+        assert_eq!(first, expected.state[0]);
+        expected.permute();
+        expected.state[0] += GF::from(98);
+        expected.permute();
+        assert_eq!(second, expected.state[0]);
+    }
+
+    #[test]
+    fn test_from_prepared() {
+        let prepared = PreparedParameters::<GF>::new(&PARAMS);
+        let mut owned = Poseidon::from_prepared(prepared);
+        let mut borrowed = Poseidon::<GF>::new(&PARAMS);
+        owned.absorb(&GF::from(7));
+        borrowed.absorb(&GF::from(7));
+        assert_eq!(owned.squeeze(), borrowed.squeeze());
+    }
+
+    #[test]
+    fn test_hash_batch() {
+        let inputs: Vec<GF> = (0..6).map(GF::from).collect();
+        let expected = [
+            hash(&inputs[..2], &PARAMS).unwrap(),
+            hash(&inputs[2..4], &PARAMS).unwrap(),
+            hash(&inputs[4..], &PARAMS).unwrap(),
+        ]
+        .concat();
+        assert_eq!(hash_batch(&inputs, 2, &PARAMS).unwrap(), expected);
+        assert_eq!(hash_batch(&inputs[..0], 2, &PARAMS).unwrap(), vec![]);
+        assert!(hash_batch(&inputs, 0, &PARAMS).is_err());
+        assert!(hash_batch(&inputs, 3, &PARAMS).is_err());
+        assert!(hash_batch(&inputs, 4, &PARAMS).is_err());
+    }
 }
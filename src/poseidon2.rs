@@ -0,0 +1,188 @@
+//! Poseidon2 permutation and sponge.
+//!
+//! Poseidon2 replaces the MDS matrix of Poseidon by two cheap linear layers:
+//! an external matrix in full rounds, also applied once before the first
+//! round, and an internal matrix 1 + diag(d) in partial rounds, which only
+//! costs t multiplications. Partial rounds add a single round constant and
+//! apply the S-box to the first element of the state.
+//!
+//! The sponge follows [`crate::Poseidon`]: the rate comes first in the state
+//! and the capacity last.
+//!
+//! ```
+//! use poseidon::poseidon2;
+//! use poseidon::parameters::poseidon2::pallas::{prepared, GF};
+//! let inputs = vec![GF::from(7), GF::from(54)];
+//! let h = poseidon2::hash(&inputs, prepared()).unwrap();
+//! ```
+
+use crate::fixed::permute2_rounds;
+use crate::parameters::{PreparedPoseidon2Parameters, ToPrepared};
+use crate::permutation::SpongeMode;
+use alloc::{
+    borrow::Cow,
+    string::{String, ToString},
+    vec::Vec,
+};
+use ff::PrimeField;
+
+pub struct Poseidon2<'a, GF: Clone> {
+    params: Cow<'a, PreparedPoseidon2Parameters<GF>>,
+    mode: SpongeMode,
+    offset: usize,
+    state: Vec<GF>,
+    // Buffer of the external mixes, so that permuting does not allocate.
+    scratch: Vec<GF>,
+}
+
+impl<'a, GF> Poseidon2<'a, GF>
+where
+    GF: PrimeField,
+{
+    pub fn new<P>(params: &'a P) -> Self
+    where
+        P: ToPrepared<GF, PreparedPoseidon2Parameters<GF>> + ?Sized,
+    {
+        let params = params.to_prepared();
+        let width = params.rate + params.capacity;
+        Poseidon2 {
+            params,
+            mode: SpongeMode::Absorbing,
+            offset: 0,
+            state: vec![GF::ZERO; width],
+            scratch: vec![GF::ZERO; width],
+        }
+    }
+
+    /// Returns the current phase of the sponge.
+    pub fn mode(&self) -> SpongeMode {
+        self.mode
+    }
+
+    /// Permutes the state and starts a new phase at the beginning of the rate.
+    fn start_phase(&mut self, mode: SpongeMode) {
+        permute_state(&mut self.state, &self.params, &mut self.scratch);
+        self.offset = 0;
+        self.mode = mode;
+    }
+
+    /// Adds an element to the outer state, permuting beforehand as
+    /// [`crate::Poseidon::absorb`] does.
+    pub fn absorb(&mut self, input: &GF) {
+        if self.mode == SpongeMode::Squeezing || self.offset == self.params.rate {
+            self.start_phase(SpongeMode::Absorbing);
+        }
+        self.state[self.offset].add_assign(input);
+        self.offset += 1;
+    }
+
+    /// Reads an element from the outer state, permuting beforehand as
+    /// [`crate::Poseidon::squeeze`] does.
+    pub fn squeeze(&mut self) -> GF {
+        if self.mode == SpongeMode::Absorbing || self.offset == self.params.rate {
+            self.start_phase(SpongeMode::Squeezing);
+        }
+        let result = self.state[self.offset];
+        self.offset += 1;
+        result
+    }
+}
+
+fn permute_state<GF>(state: &mut [GF], params: &PreparedPoseidon2Parameters<GF>, scratch: &mut [GF])
+where
+    GF: PrimeField,
+{
+    permute2_rounds(
+        state,
+        scratch,
+        params.power,
+        params.n_full_rounds,
+        params.n_partial_rounds,
+        (&params.external_matrix, &params.internal_diagonal),
+        &params.round_constants,
+    );
+}
+
+/// Applies the Poseidon2 permutation to a caller-owned state of
+/// rate + capacity elements.
+pub fn permute<GF, P>(state: &mut [GF], params: &P) -> Result<(), String>
+where
+    GF: PrimeField,
+    P: ToPrepared<GF, PreparedPoseidon2Parameters<GF>> + ?Sized,
+{
+    let params = params.to_prepared();
+    let width = params.rate + params.capacity;
+    if state.len() != width {
+        return Err(format!(
+            "State length {} must be equal to rate + capacity = {}",
+            state.len(),
+            width
+        ));
+    }
+    permute_state(state, &params, &mut vec![GF::ZERO; width]);
+    Ok(())
+}
+
+/// Hashes inputs of length a multiple of the rate, as [`crate::hash`] does
+/// with the Poseidon permutation.
+pub fn hash<'a, GF, P>(inputs: &'a [GF], params: &'a P) -> Result<Vec<GF>, String>
+where
+    GF: PrimeField,
+    P: ToPrepared<GF, PreparedPoseidon2Parameters<GF>> + ?Sized,
+{
+    let params = params.to_prepared();
+    if inputs.is_empty() {
+        return Err("Empty inputs".to_string());
+    }
+    if !inputs.len().is_multiple_of(params.rate) {
+        return Err(format!(
+            "Input length {} must be a multiple of the hash rate {}",
+            inputs.len(),
+            params.rate
+        ));
+    }
+
+    let mut poseidon2 = Poseidon2::<GF>::new(&*params);
+    for input in inputs {
+        poseidon2.absorb(input);
+    }
+    Ok((0..params.output_size)
+        .map(|_| poseidon2.squeeze())
+        .collect())
+}
+
+#[cfg(test)]
+mod test_poseidon2 {
+    use super::*;
+    use crate::fixed::mix_internal;
+    use crate::parameters::poseidon2::pallas::{prepared, GF, PARAMS};
+
+    #[test]
+    fn test_mix_internal() {
+        // 1 + diag(1, 1, 2) = [[2, 1, 1], [1, 2, 1], [1, 1, 3]]
+        let mut state = [GF::from(1), GF::from(2), GF::from(3)];
+        mix_internal(&mut state, prepared().internal_diagonal());
+        assert_eq!(state, [GF::from(7), GF::from(8), GF::from(12)]);
+    }
+
+    #[test]
+    fn test_sponge_matches_permute() {
+        let inputs = [GF::from(7), GF::from(98)];
+        let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+        permute(&mut state, &PARAMS).unwrap();
+        let mut poseidon2 = Poseidon2::<GF>::new(&PARAMS);
+        for input in &inputs {
+            poseidon2.absorb(input);
+        }
+        assert_eq!(poseidon2.squeeze(), state[0]);
+        assert_eq!(poseidon2.squeeze(), state[1]);
+        assert_eq!(poseidon2.mode(), SpongeMode::Squeezing);
+        assert_eq!(hash(&inputs, prepared()).unwrap(), vec![state[0]]);
+    }
+
+    #[test]
+    fn test_hash_errors() {
+        assert!(hash::<GF, _>(&[], prepared()).is_err());
+        assert!(hash(&[GF::from(1)], prepared()).is_err());
+    }
+}
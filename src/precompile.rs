@@ -0,0 +1,448 @@
+//! EIP-5988 precompiled contract.
+//!
+//! [`run`] decodes a prime field, a set of parameters and a message from the
+//! call data, hashes the message as [`crate::hash`] does and returns the
+//! encoded hash value with the gas consumed. The input is a sequence of 32-byte
+//! big-endian words, in the order of [`crate::parameters::Parameters`]:
+//!
+//! | words          | content                                    |
+//! |----------------|--------------------------------------------|
+//! | 1              | modulus p, odd, of at most 256 bits        |
+//! | 1              | power α of the S-box, at most 255          |
+//! | 1              | rate r                                     |
+//! | 1              | capacity c                                 |
+//! | 1              | output size o                              |
+//! | 1              | number of full rounds R_F                  |
+//! | 1              | number of partial rounds R_P               |
+//! | t²             | MDS matrix, row by row, with t = r + c     |
+//! | (R_F + R_P)·t  | round constants, t per round               |
+//! | n              | message, n being a positive multiple of r  |
+//!
+//! Sizes must fit in 32 bits and elements must be lower than p. The output is
+//! made of o words. Primality of p and the security of the parameters are left
+//! to the caller; only the consistency checks of
+//! [`crate::parameters::Parameters::validate`] that do not involve the MDS
+//! matrix are performed.
+//!
+//! The gas cost is charged before hashing, from the header alone:
+//!
+//! gas = [`BASE_GAS`] + [`WORD_GAS`]·(input words + o) + [`MUL_GAS`]·m·P
+//!
+//! with P the number of permutations, n/r + ⌈o/r⌉ - 1, and m the number of
+//! field multiplications of a permutation, R_F·t·(s + t) + R_P·(s + t²), where
+//! s = ⌊log2 α⌋ + popcount(α) - 1 is the cost of an S-box by square-and-multiply
+//! and t² the cost of the MDS matrix.
+
+use crate::fields::runtime::{RuntimeElement, RuntimeField, BYTES};
+use crate::fixed::HashError;
+use crate::parameters::runtime::modulus_minus_one_rem;
+use crate::parameters::{gcd, ParametersError, RuntimeParameters};
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+/// Fixed cost of a call.
+pub const BASE_GAS: u64 = 60;
+/// Cost of decoding an input word or encoding an output word.
+pub const WORD_GAS: u64 = 3;
+/// Cost of a multiplication in a field of at most 256 bits.
+pub const MUL_GAS: u64 = 2;
+
+const HEADER_WORDS: usize = 7;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrecompileError {
+    /// The input length is not a multiple of 32 bytes.
+    UnalignedInput {
+        length: usize,
+    },
+    /// The input is shorter than its header and the tables it declares.
+    ShortInput {
+        length: usize,
+        expected: usize,
+    },
+    /// Parameters are followed by other words, see [`decode_parameters`].
+    LongInput {
+        length: usize,
+        expected: usize,
+    },
+    /// A header word exceeds its bound: 255 for the power, 2^32 - 1 otherwise.
+    ValueTooLarge {
+        word: usize,
+    },
+    /// The modulus is even or equal to 1.
+    InvalidModulus,
+    /// The element at the given word is not lower than the modulus.
+    NonCanonical {
+        word: usize,
+    },
+    Parameters(ParametersError),
+    Hash(HashError),
+    OutOfGas {
+        gas: u64,
+        gas_limit: u64,
+    },
+}
+
+impl fmt::Display for PrecompileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrecompileError::UnalignedInput { length } => {
+                write!(f, "Input length {} must be a multiple of {}", length, BYTES)
+            }
+            PrecompileError::ShortInput { length, expected } => {
+                write!(f, "Input length {} must be at least {}", length, expected)
+            }
+            PrecompileError::LongInput { length, expected } => {
+                write!(f, "Input length {} must be at most {}", length, expected)
+            }
+            PrecompileError::ValueTooLarge { word } => write!(f, "Word {} is too large", word),
+            PrecompileError::InvalidModulus => write!(f, "Modulus must be odd and greater than 1"),
+            PrecompileError::NonCanonical { word } => {
+                write!(f, "Word {} must be lower than the modulus", word)
+            }
+            PrecompileError::Parameters(error) => error.fmt(f),
+            PrecompileError::Hash(error) => error.fmt(f),
+            PrecompileError::OutOfGas { gas, gas_limit } => {
+                write!(f, "Gas {} exceeds the limit {}", gas, gas_limit)
+            }
+        }
+    }
+}
+
+/// Field and sizes decoded from the header of an input.
+struct Header {
+    field: RuntimeField,
+    power: u8,
+    rate: usize,
+    capacity: usize,
+    output_size: usize,
+    n_full_rounds: usize,
+    n_partial_rounds: usize,
+    /// Number of words of the header and the tables.
+    n_words: usize,
+}
+
+fn word(input: &[u8], index: usize) -> &[u8; BYTES] {
+    input[index * BYTES..(index + 1) * BYTES]
+        .try_into()
+        .unwrap()
+}
+
+/// Reads a header word, which must not exceed `max`.
+fn small(input: &[u8], index: usize, max: u32) -> Result<usize, PrecompileError> {
+    let (high, low) = word(input, index).split_at(BYTES - 4);
+    let value = u32::from_be_bytes(low.try_into().unwrap());
+    if high.iter().any(|&b| b != 0) || value > max {
+        return Err(PrecompileError::ValueTooLarge { word: index });
+    }
+    Ok(value as usize)
+}
+
+/// Reads the elements of a range of words.
+fn elements(
+    field: &RuntimeField,
+    input: &[u8],
+    words: Range<usize>,
+) -> Result<Vec<RuntimeElement>, PrecompileError> {
+    words
+        .map(|i| {
+            field
+                .element_from_be_bytes(word(input, i))
+                .ok_or(PrecompileError::NonCanonical { word: i })
+        })
+        .collect()
+}
+
+impl Header {
+    /// Decodes the header of an input, which must hold the tables it declares.
+    fn decode(input: &[u8]) -> Result<Self, PrecompileError> {
+        if !input.len().is_multiple_of(BYTES) {
+            return Err(PrecompileError::UnalignedInput {
+                length: input.len(),
+            });
+        }
+        if input.len() < HEADER_WORDS * BYTES {
+            return Err(PrecompileError::ShortInput {
+                length: input.len(),
+                expected: HEADER_WORDS * BYTES,
+            });
+        }
+
+        let field =
+            RuntimeField::from_be_bytes(word(input, 0)).ok_or(PrecompileError::InvalidModulus)?;
+        let power = small(input, 1, u8::MAX as u32)? as u8;
+        let rate = small(input, 2, u32::MAX)?;
+        let capacity = small(input, 3, u32::MAX)?;
+        let output_size = small(input, 4, u32::MAX)?;
+        let n_full_rounds = small(input, 5, u32::MAX)?;
+        let n_partial_rounds = small(input, 6, u32::MAX)?;
+
+        let invalid = |error| Err(PrecompileError::Parameters(error));
+        if rate == 0 {
+            return invalid(ParametersError::ZeroRate);
+        }
+        if capacity == 0 {
+            return invalid(ParametersError::ZeroCapacity);
+        }
+        if output_size == 0 {
+            return invalid(ParametersError::ZeroOutputSize);
+        }
+        if power < 2 || gcd(power as u64, modulus_minus_one_rem(&field, power)) != 1 {
+            return invalid(ParametersError::InvalidPower { power });
+        }
+
+        // Sizes fit in 32 bits, so that table lengths fit in 128 bits.
+        let width = rate as u128 + capacity as u128;
+        let n_rounds = n_full_rounds as u128 + n_partial_rounds as u128;
+        let expected = HEADER_WORDS as u128 + width * width + n_rounds * width;
+        if ((input.len() / BYTES) as u128) < expected {
+            return Err(PrecompileError::ShortInput {
+                length: input.len(),
+                expected: usize::try_from(expected * BYTES as u128).unwrap_or(usize::MAX),
+            });
+        }
+
+        Ok(Header {
+            field,
+            power,
+            rate,
+            capacity,
+            output_size,
+            n_full_rounds,
+            n_partial_rounds,
+            // The tables fit in the input, so that their length fits in usize.
+            n_words: expected as usize,
+        })
+    }
+
+    /// Returns the gas cost of hashing `n_inputs` elements, the input being
+    /// of `n_words` words.
+    fn gas(&self, n_words: usize, n_inputs: usize) -> u64 {
+        let power = self.power;
+        let sbox = (7 - power.leading_zeros() + power.count_ones() - 1) as u128;
+        let t = (self.rate + self.capacity) as u128;
+        let n_muls = self.n_full_rounds as u128 * t * (sbox + t)
+            + (self.n_partial_rounds as u128).saturating_mul(sbox + t * t);
+        let n_permutations =
+            (n_inputs / self.rate + self.output_size.div_ceil(self.rate) - 1) as u128;
+        let gas = (BASE_GAS as u128)
+            .saturating_add(WORD_GAS as u128 * (n_words as u128 + self.output_size as u128))
+            .saturating_add(
+                (MUL_GAS as u128)
+                    .saturating_mul(n_muls)
+                    .saturating_mul(n_permutations),
+            );
+        u64::try_from(gas).unwrap_or(u64::MAX)
+    }
+
+    /// Reads the tables, without checking the MDS matrix.
+    fn parameters(self, input: &[u8]) -> Result<RuntimeParameters, PrecompileError> {
+        let width = self.rate + self.capacity;
+        let mds_end = HEADER_WORDS + width * width;
+        Ok(RuntimeParameters {
+            mds_matrix: elements(&self.field, input, HEADER_WORDS..mds_end)?,
+            round_constants: elements(&self.field, input, mds_end..self.n_words)?,
+            field: self.field,
+            power: self.power,
+            rate: self.rate,
+            capacity: self.capacity,
+            output_size: self.output_size,
+            n_partial_rounds: self.n_partial_rounds,
+            n_full_rounds: self.n_full_rounds,
+        })
+    }
+}
+
+/// Decodes the header of a call, checks the length of its message and returns
+/// its gas cost.
+fn decode_call(input: &[u8]) -> Result<(Header, u64), PrecompileError> {
+    let header = Header::decode(input)?;
+    let n_words = input.len() / BYTES;
+    let n_inputs = n_words - header.n_words;
+    if n_inputs == 0 {
+        return Err(PrecompileError::Hash(HashError::EmptyInputs));
+    }
+    if !n_inputs.is_multiple_of(header.rate) {
+        return Err(PrecompileError::Hash(HashError::InputLength {
+            length: n_inputs,
+            rate: header.rate,
+        }));
+    }
+    let gas = header.gas(n_words, n_inputs);
+    Ok((header, gas))
+}
+
+/// Returns the gas cost of a call, without running it.
+pub fn required_gas(input: &[u8]) -> Result<u64, PrecompileError> {
+    Ok(decode_call(input)?.1)
+}
+
+/// Runs the precompile, returning the encoded hash value and the gas used.
+///
+/// Fails without hashing if the gas cost exceeds `gas_limit`.
+pub fn run(input: &[u8], gas_limit: u64) -> Result<(Vec<u8>, u64), PrecompileError> {
+    let (header, gas) = decode_call(input)?;
+    if gas > gas_limit {
+        return Err(PrecompileError::OutOfGas { gas, gas_limit });
+    }
+
+    let message = header.n_words..input.len() / BYTES;
+    let params = header.parameters(input)?;
+    let inputs = elements(params.field(), input, message)?;
+    let output = params.hash(&inputs).map_err(PrecompileError::Hash)?;
+    let output = output
+        .iter()
+        .flat_map(|x| params.field().element_to_be_bytes(x))
+        .collect();
+    Ok((output, gas))
+}
+
+/// Decodes parameters laid out as the input of a call without its message.
+///
+/// Unlike [`run`], checks the MDS matrix, see
+/// [`RuntimeParameters::validate`].
+pub fn decode_parameters(input: &[u8]) -> Result<RuntimeParameters, PrecompileError> {
+    let header = Header::decode(input)?;
+    if input.len() > header.n_words * BYTES {
+        return Err(PrecompileError::LongInput {
+            length: input.len(),
+            expected: header.n_words * BYTES,
+        });
+    }
+    let params = header.parameters(input)?;
+    params.validate().map_err(PrecompileError::Parameters)?;
+    Ok(params)
+}
+
+#[cfg(test)]
+mod test_precompile {
+    use super::*;
+
+    fn be_word(x: u64) -> [u8; BYTES] {
+        let mut bytes = [0u8; BYTES];
+        bytes[BYTES - 8..].copy_from_slice(&x.to_be_bytes());
+        bytes
+    }
+
+    /// Encodes a call over p = 2^64 + 13 with the identity as MDS matrix.
+    fn input(header: [u64; 6], n_inputs: usize) -> Vec<u8> {
+        let mut input = vec![0u8; BYTES];
+        input[BYTES - 9] = 1;
+        input[BYTES - 1] = 13;
+        for value in header {
+            input.extend_from_slice(&be_word(value));
+        }
+        let [_, rate, capacity, _, n_full_rounds, n_partial_rounds] = header.map(|x| x as usize);
+        let width = rate + capacity;
+        for i in 0..width * width {
+            input.extend_from_slice(&be_word((i % (width + 1) == 0) as u64));
+        }
+        for i in 0..(n_full_rounds + n_partial_rounds) * width + n_inputs {
+            input.extend_from_slice(&be_word(i as u64));
+        }
+        input
+    }
+
+    #[test]
+    fn test_run() {
+        // Two full rounds of width 2 with the identity matrix, constants
+        // (0, 1) and (2, 3), and the message 4. p - 1 = 2^64 + 12 is coprime
+        // to 5 and the first element stays lower than p.
+        let input = input([5, 1, 1, 1, 2, 0], 1);
+        let (output, gas) = run(&input, u64::MAX).unwrap();
+        let x = (4u128.pow(5) + 2).pow(5);
+        assert_eq!(output, be_word(x as u64));
+        // s = 2 + 2 - 1 = 3, m = 2·2·(3 + 2) = 20, one permutation, 16 input
+        // words and one output word.
+        assert_eq!(gas, BASE_GAS + WORD_GAS * (16 + 1) + MUL_GAS * 20);
+        assert_eq!(required_gas(&input), Ok(gas));
+        assert_eq!(
+            run(&input, gas - 1),
+            Err(PrecompileError::OutOfGas {
+                gas,
+                gas_limit: gas - 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |input: &[u8]| run(input, u64::MAX).unwrap_err();
+        assert_eq!(
+            error(&[0; 33]),
+            PrecompileError::UnalignedInput { length: 33 }
+        );
+        assert_eq!(
+            error(&[0; 64]),
+            PrecompileError::ShortInput {
+                length: 64,
+                expected: 224
+            }
+        );
+        let valid = input([5, 1, 1, 1, 2, 0], 1);
+        let mut even = valid.clone();
+        even[BYTES - 1] = 14;
+        assert_eq!(error(&even), PrecompileError::InvalidModulus);
+        let mut large = valid.clone();
+        large[2 * BYTES - 2] = 1;
+        assert_eq!(error(&large), PrecompileError::ValueTooLarge { word: 1 });
+        assert_eq!(
+            error(&input([7, 1, 1, 1, 2, 0], 1)),
+            PrecompileError::Parameters(ParametersError::InvalidPower { power: 7 })
+        );
+        assert_eq!(
+            error(&input([5, 0, 1, 1, 2, 0], 1)),
+            PrecompileError::Parameters(ParametersError::ZeroRate)
+        );
+        assert_eq!(
+            error(&input([5, 1, 1, 1, 2, 0], 0)),
+            PrecompileError::Hash(HashError::EmptyInputs)
+        );
+        assert_eq!(
+            error(&input([5, 2, 1, 1, 2, 0], 3)),
+            PrecompileError::Hash(HashError::InputLength { length: 3, rate: 2 })
+        );
+        let mut non_canonical = valid.clone();
+        non_canonical[valid.len() - 9] = 1;
+        non_canonical[valid.len() - 1] = 13;
+        assert_eq!(
+            error(&non_canonical),
+            PrecompileError::NonCanonical { word: 15 }
+        );
+    }
+
+    #[test]
+    fn test_decode_parameters() {
+        let input = input([5, 1, 1, 1, 2, 0], 1);
+        let (parameters, message) = input.split_at(input.len() - BYTES);
+        let params = decode_parameters(parameters).unwrap();
+        let inputs = [params
+            .field()
+            .element_from_be_bytes(word(message, 0))
+            .unwrap()];
+        let output = params.hash(&inputs).unwrap();
+        assert_eq!(
+            params.field().element_to_be_bytes(&output[0]).to_vec(),
+            run(&input, u64::MAX).unwrap().0
+        );
+        assert_eq!(
+            decode_parameters(&input).err(),
+            Some(PrecompileError::LongInput {
+                length: 16 * BYTES,
+                expected: 15 * BYTES
+            })
+        );
+        // Rows (0, 1) and (0, 1).
+        let mut singular = parameters.to_vec();
+        singular[8 * BYTES - 1] = 0;
+        singular[9 * BYTES - 1] = 1;
+        assert_eq!(
+            decode_parameters(&singular).err(),
+            Some(PrecompileError::Parameters(
+                ParametersError::SingularMdsMatrix
+            ))
+        );
+        assert!(run(&[&singular, message].concat(), u64::MAX).is_ok());
+    }
+}
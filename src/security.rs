@@ -0,0 +1,401 @@
+//! Round numbers and security margins.
+//!
+//! Follows `calc_round_numbers.py` of the Poseidon reference implementation,
+//! for x^α S-boxes. Full rounds R_F and partial rounds R_P are secure at M bits
+//! of security if R_F reaches the bounds of section 5.5 of the Poseidon paper:
+//!
+//! - statistical attacks: 6, or 10 if M > (⌊log2(p) - (α - 1)/2⌋)·(t + 1),
+//! - interpolation: 1 + ⌈log_α(2)·min(M, n)⌉ + ⌈log_α(t)⌉ - R_P,
+//! - Gröbner bases: log_α(2)·min(M, log2(p)) - R_P,
+//!   t - 1 + log_α(2)·min(M/(t + 1), log2(p)/2) - R_P and
+//!   (t - 2 + M/(2·log2(α)) - R_P)/(t - 1),
+//!
+//! n being ⌈log2(p)⌉, and if the algebraic attack of "On the Algebraic
+//! Degree of Iterated Power Functions" (eprint 2023/537), whose cost is
+//! estimated as the square of a binomial coefficient, costs at least 2^M.
+//!
+//! Recommended round numbers minimize the number of S-boxes, t·R_F + R_P,
+//! then add the security margin of the paper: 2 full rounds and 7.5% of
+//! partial rounds.
+//!
+//! ```
+//! use poseidon::parameters::s128b::{GF, PARAMS};
+//! use poseidon::security::report;
+//! let report = report::<GF>(&PARAMS, 128);
+//! assert!(report.secure);
+//! ```
+
+use crate::convert::le_bytes;
+use crate::parameters::Parameters;
+use core::f64::consts::LN_2;
+use ff::PrimeField;
+
+/// Full rounds are searched below this bound, as in the reference.
+const MAX_FULL_ROUNDS: usize = 100;
+/// Partial rounds are searched below this bound, as in the reference.
+const MAX_PARTIAL_ROUNDS: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundNumbers {
+    pub n_full_rounds: usize,
+    pub n_partial_rounds: usize,
+}
+
+/// Security of a set of parameters at a given level.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SecurityReport {
+    pub security_level: usize,
+    /// Whether the round numbers of the parameters are secure.
+    pub secure: bool,
+    /// Full rounds in excess of the minimal secure number of full rounds,
+    /// keeping the partial rounds of the parameters. Negative if insecure,
+    /// `None` if no number of full rounds is secure.
+    pub full_rounds_margin: Option<isize>,
+    /// Partial rounds in excess of the minimal secure number of partial
+    /// rounds, keeping the full rounds of the parameters.
+    pub partial_rounds_margin: Option<isize>,
+    /// Recommended round numbers for the field, width and S-box.
+    pub recommended: Option<RoundNumbers>,
+}
+
+/// Returns whether the round numbers resist the attacks of the module for a
+/// field of log2(p) bits, a width t and a x^α S-box.
+///
+/// Widths lower than 2 are rejected, the last Gröbner bound dividing by t - 1.
+pub fn is_secure(
+    log2_p: f64,
+    width: usize,
+    power: u8,
+    security_level: usize,
+    n_full_rounds: usize,
+    n_partial_rounds: usize,
+) -> bool {
+    if width < 2 {
+        return false;
+    }
+    let (t, alpha, m) = (width as f64, power as f64, security_level as f64);
+    let (r_f, r_p) = (n_full_rounds as f64, n_partial_rounds as f64);
+    let n = ceil(log2_p) as f64;
+    let log_alpha_2 = 1.0 / log2(alpha);
+
+    let statistical = if m <= floor(log2_p - (alpha - 1.0) / 2.0) as f64 * (t + 1.0) {
+        6
+    } else {
+        10
+    };
+    let interpolation = 1 + ceil(log_alpha_2 * min(m, n)) + ceil_log(width, power as usize) as i64
+        - n_partial_rounds as i64;
+    let groebner_1 = ceil(log_alpha_2 * min(m, log2_p) - r_p);
+    let groebner_2 = ceil(t - 1.0 + log_alpha_2 * min(m / (t + 1.0), log2_p / 2.0) - r_p);
+    let groebner_3 = ceil((t - 2.0 + m / (2.0 * log2(alpha)) - r_p) / (t - 1.0));
+    let bound = [
+        statistical,
+        interpolation,
+        groebner_1,
+        groebner_2,
+        groebner_3,
+    ]
+    .into_iter()
+    .max()
+    .unwrap();
+
+    let r = floor(t / 3.0) as f64;
+    let over = (r_f - 1.0) * t + r_p + r + r * (r_f / 2.0) + r_p + alpha;
+    let under = r * (r_f / 2.0) + r_p + alpha;
+    let algebraic = ceil(2.0 * log2_binomial(over, under));
+
+    n_full_rounds as i64 >= bound && algebraic >= security_level as i64
+}
+
+/// Returns the secure round numbers with the fewest S-boxes, full rounds
+/// being even.
+pub fn min_round_numbers(
+    log2_p: f64,
+    width: usize,
+    power: u8,
+    security_level: usize,
+) -> Option<RoundNumbers> {
+    cheapest_round_numbers(log2_p, width, power, security_level, |rounds| rounds)
+}
+
+/// Returns the round numbers recommended by the reference: the secure round
+/// numbers with the fewest S-boxes once the security margin is added.
+pub fn recommended_round_numbers(
+    log2_p: f64,
+    width: usize,
+    power: u8,
+    security_level: usize,
+) -> Option<RoundNumbers> {
+    cheapest_round_numbers(log2_p, width, power, security_level, |rounds| {
+        RoundNumbers {
+            n_full_rounds: rounds.n_full_rounds + 2,
+            n_partial_rounds: ceil(rounds.n_partial_rounds as f64 * 1.075) as usize,
+        }
+    })
+}
+
+/// For each number of partial rounds, takes the fewest secure full rounds,
+/// adjusts the round numbers, and keeps the cheapest ones, with the fewest
+/// full rounds on equal costs.
+fn cheapest_round_numbers<F>(
+    log2_p: f64,
+    width: usize,
+    power: u8,
+    security_level: usize,
+    adjust: F,
+) -> Option<RoundNumbers>
+where
+    F: Fn(RoundNumbers) -> RoundNumbers,
+{
+    if width < 2 {
+        return None;
+    }
+    let mut best: Option<(usize, RoundNumbers)> = None;
+    for n_partial_rounds in 1..MAX_PARTIAL_ROUNDS {
+        let n_full_rounds = match (4..MAX_FULL_ROUNDS)
+            .step_by(2)
+            .find(|&r_f| is_secure(log2_p, width, power, security_level, r_f, n_partial_rounds))
+        {
+            Some(n_full_rounds) => n_full_rounds,
+            None => continue,
+        };
+        let rounds = adjust(RoundNumbers {
+            n_full_rounds,
+            n_partial_rounds,
+        });
+        let cost = width * rounds.n_full_rounds + rounds.n_partial_rounds;
+        let better = match best {
+            None => true,
+            Some((min_cost, min_rounds)) => {
+                cost < min_cost
+                    || (cost == min_cost && rounds.n_full_rounds < min_rounds.n_full_rounds)
+            }
+        };
+        if better {
+            best = Some((cost, rounds));
+        }
+    }
+    best.map(|(_, rounds)| rounds)
+}
+
+/// Reports the security of parameters over the field `GF`.
+pub fn report<GF>(params: &Parameters, security_level: usize) -> SecurityReport
+where
+    GF: PrimeField,
+{
+    let log2_p = log2_modulus::<GF>();
+    let width = params.rate + params.capacity;
+    let secure_with = |n_full_rounds, n_partial_rounds| {
+        is_secure(
+            log2_p,
+            width,
+            params.power,
+            security_level,
+            n_full_rounds,
+            n_partial_rounds,
+        )
+    };
+    let full_rounds_margin = (0..MAX_FULL_ROUNDS)
+        .step_by(2)
+        .find(|&r_f| secure_with(r_f, params.n_partial_rounds))
+        .map(|r_f| params.n_full_rounds as isize - r_f as isize);
+    let partial_rounds_margin = (0..MAX_PARTIAL_ROUNDS)
+        .find(|&r_p| secure_with(params.n_full_rounds, r_p))
+        .map(|r_p| params.n_partial_rounds as isize - r_p as isize);
+    SecurityReport {
+        security_level,
+        secure: secure_with(params.n_full_rounds, params.n_partial_rounds),
+        full_rounds_margin,
+        partial_rounds_margin,
+        recommended: recommended_round_numbers(log2_p, width, params.power, security_level),
+    }
+}
+
+/// Returns log2(p) for the modulus p of `GF`.
+pub fn log2_modulus<GF>() -> f64
+where
+    GF: PrimeField,
+{
+    // p - 1 and p only differ in their last bit, below the precision of f64.
+    let bytes = le_bytes(&-GF::ONE);
+    let top = bytes.iter().rposition(|&b| b != 0).unwrap_or(0);
+    let low = top.saturating_sub(7);
+    let mantissa = bytes[low..=top]
+        .iter()
+        .rev()
+        .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    log2(mantissa as f64) + (8 * low) as f64
+}
+
+// Floating-point functions, which are not available in `core`.
+
+fn min(a: f64, b: f64) -> f64 {
+    if a < b {
+        a
+    } else {
+        b
+    }
+}
+
+fn floor(x: f64) -> i64 {
+    let i = x as i64;
+    if (i as f64) > x {
+        i - 1
+    } else {
+        i
+    }
+}
+
+fn ceil(x: f64) -> i64 {
+    let i = x as i64;
+    if (i as f64) < x {
+        i + 1
+    } else {
+        i
+    }
+}
+
+/// Returns ⌈log_b(x)⌉ for integers, exactly.
+fn ceil_log(x: usize, b: usize) -> u32 {
+    let mut k = 0;
+    let mut power = 1;
+    while power < x {
+        power *= b;
+        k += 1;
+    }
+    k
+}
+
+fn ln(x: f64) -> f64 {
+    // x = m·2^e with m in [1, 2), and ln(m) = 2·atanh((m - 1)/(m + 1)).
+    let bits = x.to_bits();
+    let e = ((bits >> 52) & 0x7ff) as i64 - 1023;
+    let m = f64::from_bits((bits & ((1 << 52) - 1)) | (1023 << 52));
+    let s = (m - 1.0) / (m + 1.0);
+    let s2 = s * s;
+    let mut term = s;
+    let mut sum = 0.0;
+    for k in 0..30 {
+        sum += term / (2 * k + 1) as f64;
+        term *= s2;
+    }
+    2.0 * sum + e as f64 * LN_2
+}
+
+fn log2(x: f64) -> f64 {
+    ln(x) / LN_2
+}
+
+/// ln Γ(x) for x > 0, with Stirling's series.
+fn ln_gamma(mut x: f64) -> f64 {
+    let mut shift = 0.0;
+    while x < 20.0 {
+        shift -= ln(x);
+        x += 1.0;
+    }
+    let x2 = x * x;
+    let series = 1.0 / (12.0 * x) - 1.0 / (360.0 * x * x2) + 1.0 / (1260.0 * x * x2 * x2)
+        - 1.0 / (1680.0 * x * x2 * x2 * x2);
+    shift + (x - 0.5) * ln(x) - x + 0.5 * ln(2.0 * core::f64::consts::PI) + series
+}
+
+fn log2_binomial(n: f64, k: f64) -> f64 {
+    (ln_gamma(n + 1.0) - ln_gamma(k + 1.0) - ln_gamma(n - k + 1.0)) / LN_2
+}
+
+#[cfg(test)]
+mod test_security {
+    use super::*;
+    use crate::parameters::pallas::GF;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!(a - b < 1e-12 && b - a < 1e-12, "{} != {}", a, b);
+    }
+
+    #[test]
+    fn test_float_functions() {
+        assert_close(ln(10.0), core::f64::consts::LN_10);
+        assert_close(log2(3.0), 1.584962500721156);
+        assert_eq!(log2(1024.0), 10.0);
+        assert_eq!(ceil_log(9, 3), 2);
+        assert_eq!(ceil_log(10, 3), 3);
+        assert_eq!(
+            (floor(-0.5), ceil(-0.5), floor(2.0), ceil(2.5)),
+            (-1, 0, 2, 3)
+        );
+        // C(10, 3) = 120 and C(100, 50) = 1.0089134454556417e29
+        assert_close(log2_binomial(10.0, 3.0), log2(120.0));
+        assert_close(log2_binomial(100.0, 50.0), log2(1.0089134454556417e29));
+    }
+
+    #[test]
+    fn test_log2_modulus() {
+        // p = 2^254 + 45560315531419706090280762371685220353
+        assert_eq!(log2_modulus::<GF>(), 254.0);
+    }
+
+    #[test]
+    fn test_recommended_round_numbers() {
+        // Round numbers of the instances of the Poseidon2 reference
+        // implementation, which follows the same script.
+        let rounds = |n_full_rounds, n_partial_rounds| {
+            Some(RoundNumbers {
+                n_full_rounds,
+                n_partial_rounds,
+            })
+        };
+        let pallas = log2_modulus::<GF>();
+        assert_eq!(recommended_round_numbers(pallas, 3, 5, 128), rounds(8, 56));
+        assert_eq!(recommended_round_numbers(pallas, 4, 5, 128), rounds(8, 56));
+        assert_eq!(recommended_round_numbers(pallas, 8, 5, 128), rounds(8, 57));
+        let goldilocks = log2(((1u128 << 64) - (1 << 32) + 1) as f64);
+        assert_eq!(
+            recommended_round_numbers(goldilocks, 8, 7, 128),
+            rounds(8, 22)
+        );
+        assert_eq!(
+            recommended_round_numbers(goldilocks, 24, 7, 128),
+            rounds(8, 22)
+        );
+        let babybear = log2(((1u64 << 31) - (1 << 27) + 1) as f64);
+        assert_eq!(
+            recommended_round_numbers(babybear, 16, 7, 128),
+            rounds(8, 13)
+        );
+        assert_eq!(
+            recommended_round_numbers(babybear, 24, 7, 128),
+            rounds(8, 21)
+        );
+    }
+
+    #[test]
+    fn test_min_round_numbers() {
+        let log2_p = log2_modulus::<GF>();
+        let rounds = min_round_numbers(log2_p, 3, 5, 128).unwrap();
+        assert!(is_secure(
+            log2_p,
+            3,
+            5,
+            128,
+            rounds.n_full_rounds,
+            rounds.n_partial_rounds
+        ));
+        assert!(!is_secure(
+            log2_p,
+            3,
+            5,
+            128,
+            rounds.n_full_rounds,
+            rounds.n_partial_rounds - 1
+        ));
+    }
+
+    #[test]
+    fn test_narrow_width() {
+        let log2_p = log2_modulus::<GF>();
+        assert!(!is_secure(log2_p, 1, 5, 128, 8, 56));
+        assert!(!is_secure(log2_p, 0, 5, 128, 8, 56));
+        assert_eq!(min_round_numbers(log2_p, 1, 5, 128), None);
+        assert_eq!(recommended_round_numbers(log2_p, 1, 5, 128), None);
+    }
+}
@@ -0,0 +1,364 @@
+//! Sponge API for Field Elements (SAFE).
+//!
+//! Implements the interface described in "SAFE (Sponge API for Field
+//! Elements) - A Toolbox for ZK Hash Applications" with the Poseidon
+//! permutation.
+//! A sponge is started with an IO pattern, the sequence of absorb and squeeze
+//! calls it will go through, and a domain separator. Both are hashed into a tag
+//! initializing the capacity, so that sponges following distinct patterns
+//! produce unrelated outputs. Calls deviating from the declared pattern are
+//! rejected and `finish` fails unless the pattern was completed.
+//!
+//! Unlike [`crate::Poseidon`], the sponge tracks its absorb and squeeze
+//! positions as the pseudocode of the specification does. Squeezing after an
+//! absorption permutes the state, but absorbing after a squeeze resumes at the
+//! absorb position without permuting.
+
+use crate::parameters::{PreparedParameters, ToPrepared};
+use crate::permutation::permute_state;
+use alloc::{borrow::Cow, vec::Vec};
+use core::fmt;
+use ff::PrimeField;
+use sha3::{Digest, Sha3_256};
+
+/// A single call of an IO pattern, with the number of elements it processes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongeOp {
+    Absorb(u32),
+    Squeeze(u32),
+}
+
+impl SpongeOp {
+    fn len(&self) -> u32 {
+        match *self {
+            SpongeOp::Absorb(n) | SpongeOp::Squeeze(n) => n,
+        }
+    }
+
+    /// Encodes the call as a 32-bit word, setting the top bit for absorptions.
+    fn encode(&self) -> u32 {
+        match *self {
+            SpongeOp::Absorb(n) => n | 0x8000_0000,
+            SpongeOp::Squeeze(n) => n,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SpongeError {
+    /// The IO pattern is empty, contains a call of length 0, or a call whose
+    /// length does not fit in 31 bits.
+    InvalidPattern,
+    /// A call does not match the next call of the IO pattern, which is `None`
+    /// once the pattern is completed.
+    UnexpectedCall {
+        expected: Option<SpongeOp>,
+        got: SpongeOp,
+    },
+    /// `finish` was called with calls of the IO pattern remaining.
+    IncompletePattern { remaining: usize },
+}
+
+impl fmt::Display for SpongeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SpongeError::InvalidPattern => write!(f, "Invalid IO pattern"),
+            SpongeError::UnexpectedCall {
+                expected: Some(expected),
+                got,
+            } => write!(f, "Expected {:?}, got {:?}", expected, got),
+            SpongeError::UnexpectedCall {
+                expected: None,
+                got,
+            } => write!(f, "IO pattern already completed, got {:?}", got),
+            SpongeError::IncompletePattern { remaining } => {
+                write!(f, "IO pattern not completed, {} calls remaining", remaining)
+            }
+        }
+    }
+}
+
+/// Computes the SAFE tag of an IO pattern and a domain separator.
+///
+/// Consecutive calls of the same kind are aggregated, each call is encoded as a
+/// big-endian 32-bit word and the domain separator is appended. The tag is the
+/// first 128 bits of the SHA3-256 digest of these bytes, read as a big-endian
+/// integer. The IO pattern is assumed valid, as checked by [`Sponge::start`].
+pub fn tag<GF>(io_pattern: &[SpongeOp], domain_separator: &[u8]) -> GF
+where
+    GF: PrimeField,
+{
+    let mut hasher = Sha3_256::new();
+    let mut current: Option<SpongeOp> = None;
+    for op in io_pattern {
+        current = match (current, *op) {
+            (Some(SpongeOp::Absorb(n)), SpongeOp::Absorb(m)) => Some(SpongeOp::Absorb(n + m)),
+            (Some(SpongeOp::Squeeze(n)), SpongeOp::Squeeze(m)) => Some(SpongeOp::Squeeze(n + m)),
+            (Some(previous), op) => {
+                hasher.update(previous.encode().to_be_bytes());
+                Some(op)
+            }
+            (None, op) => Some(op),
+        };
+    }
+    if let Some(last) = current {
+        hasher.update(last.encode().to_be_bytes());
+    }
+    hasher.update(domain_separator);
+    let digest = hasher.finalize();
+
+    let mut hi = [0u8; 8];
+    let mut lo = [0u8; 8];
+    hi.copy_from_slice(&digest[..8]);
+    lo.copy_from_slice(&digest[8..16]);
+    let shift = GF::from(1u64 << 32).square();
+    GF::from(u64::from_be_bytes(hi)) * shift + GF::from(u64::from_be_bytes(lo))
+}
+
+pub struct Sponge<'a, GF: Clone> {
+    params: Cow<'a, PreparedParameters<GF>>,
+    state: Vec<GF>,
+    // Buffer of the mixes, so that permuting does not allocate.
+    scratch: Vec<GF>,
+    absorb_pos: usize,
+    squeeze_pos: usize,
+    io_pattern: Vec<SpongeOp>,
+    position: usize,
+}
+
+impl<'a, GF> Sponge<'a, GF>
+where
+    GF: PrimeField,
+{
+    /// Starts a sponge declaring its IO pattern and domain separator.
+    pub fn start<P>(
+        io_pattern: &[SpongeOp],
+        domain_separator: &[u8],
+        params: &'a P,
+    ) -> Result<Self, SpongeError>
+    where
+        P: ToPrepared<GF> + ?Sized,
+    {
+        if io_pattern.is_empty() {
+            return Err(SpongeError::InvalidPattern);
+        }
+        // The sum of aggregated calls must fit in 31 bits for the encoding.
+        let mut aggregated = 0u32;
+        let mut previous: Option<&SpongeOp> = None;
+        for op in io_pattern {
+            if op.len() == 0 {
+                return Err(SpongeError::InvalidPattern);
+            }
+            let same_kind = matches!(
+                (previous, op),
+                (Some(SpongeOp::Absorb(_)), SpongeOp::Absorb(_))
+                    | (Some(SpongeOp::Squeeze(_)), SpongeOp::Squeeze(_))
+            );
+            aggregated = if same_kind {
+                aggregated.checked_add(op.len())
+            } else {
+                Some(op.len())
+            }
+            .filter(|n| *n < 0x8000_0000)
+            .ok_or(SpongeError::InvalidPattern)?;
+            previous = Some(op);
+        }
+
+        let params = params.to_prepared();
+        let width = params.rate + params.capacity;
+        let mut state = vec![GF::ZERO; width];
+        state[params.rate] = tag(io_pattern, domain_separator);
+        Ok(Sponge {
+            params,
+            state,
+            scratch: vec![GF::ZERO; width],
+            absorb_pos: 0,
+            squeeze_pos: 0,
+            io_pattern: io_pattern.to_vec(),
+            position: 0,
+        })
+    }
+
+    fn permute(&mut self) {
+        permute_state(&mut self.state, &self.params, &mut self.scratch);
+    }
+
+    /// Checks `op` against the next call of the IO pattern and moves past it.
+    fn next_call(&mut self, op: SpongeOp) -> Result<(), SpongeError> {
+        let expected = self.io_pattern.get(self.position).copied();
+        if expected != Some(op) {
+            return Err(SpongeError::UnexpectedCall { expected, got: op });
+        }
+        self.position += 1;
+        Ok(())
+    }
+
+    /// Absorbs `inputs`, which must match the next call of the IO pattern.
+    pub fn absorb(&mut self, inputs: &[GF]) -> Result<(), SpongeError> {
+        let length = u32::try_from(inputs.len()).map_err(|_| SpongeError::InvalidPattern)?;
+        self.next_call(SpongeOp::Absorb(length))?;
+        for input in inputs {
+            if self.absorb_pos == self.params.rate {
+                self.permute();
+                self.absorb_pos = 0;
+            }
+            self.state[self.absorb_pos] += input;
+            self.absorb_pos += 1;
+        }
+        // The next squeeze permutes the absorbed inputs.
+        self.squeeze_pos = self.params.rate;
+        Ok(())
+    }
+
+    /// Squeezes `length` elements, which must match the next call of the IO
+    /// pattern.
+    pub fn squeeze(&mut self, length: u32) -> Result<Vec<GF>, SpongeError> {
+        self.next_call(SpongeOp::Squeeze(length))?;
+        let mut output = Vec::with_capacity(length as usize);
+        for _ in 0..length {
+            if self.squeeze_pos == self.params.rate {
+                self.permute();
+                self.squeeze_pos = 0;
+                self.absorb_pos = 0;
+            }
+            output.push(self.state[self.squeeze_pos]);
+            self.squeeze_pos += 1;
+        }
+        Ok(output)
+    }
+
+    /// Ends the sponge, checking that the whole IO pattern was followed.
+    pub fn finish(self) -> Result<(), SpongeError> {
+        let remaining = self.io_pattern.len() - self.position;
+        if remaining != 0 {
+            return Err(SpongeError::IncompletePattern { remaining });
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test_sponge {
+    use super::*;
+    use crate::convert::felts_from_str;
+    use crate::parameters::s128b::{GF, PARAMS};
+    use crate::permutation::permute;
+
+    #[test]
+    fn test_tag_aggregation() {
+        let split = [
+            SpongeOp::Absorb(1),
+            SpongeOp::Absorb(2),
+            SpongeOp::Squeeze(1),
+        ];
+        let aggregated = [SpongeOp::Absorb(3), SpongeOp::Squeeze(1)];
+        assert_eq!(tag::<GF>(&split, b""), tag::<GF>(&aggregated, b""));
+        assert_ne!(
+            tag::<GF>(&aggregated, b""),
+            tag::<GF>(&[SpongeOp::Absorb(3), SpongeOp::Squeeze(2)], b"")
+        );
+    }
+
+    #[test]
+    fn test_tag_domain_separator() {
+        let io_pattern = [SpongeOp::Absorb(2), SpongeOp::Squeeze(1)];
+        assert_ne!(
+            tag::<GF>(&io_pattern, b"merkle"),
+            tag::<GF>(&io_pattern, b"commitment")
+        );
+    }
+
+    #[test]
+    fn test_invalid_pattern() {
+        let start = |io_pattern: &[SpongeOp]| Sponge::<GF>::start(io_pattern, b"", &PARAMS).err();
+        assert_eq!(start(&[]), Some(SpongeError::InvalidPattern));
+        assert_eq!(
+            start(&[SpongeOp::Absorb(0), SpongeOp::Squeeze(1)]),
+            Some(SpongeError::InvalidPattern)
+        );
+        assert_eq!(
+            start(&[SpongeOp::Absorb(0x7fff_ffff), SpongeOp::Absorb(1)]),
+            Some(SpongeError::InvalidPattern)
+        );
+    }
+
+    #[test]
+    fn test_follow_pattern() {
+        let io_pattern = [
+            SpongeOp::Absorb(3),
+            SpongeOp::Squeeze(1),
+            SpongeOp::Absorb(1),
+            SpongeOp::Squeeze(2),
+        ];
+        let mut sponge = Sponge::<GF>::start(&io_pattern, b"test", &PARAMS).unwrap();
+        sponge
+            .absorb(&[GF::from(1), GF::from(2), GF::from(3)])
+            .unwrap();
+        let first = sponge.squeeze(1).unwrap();
+        sponge.absorb(&[GF::from(4)]).unwrap();
+        let second = sponge.squeeze(2).unwrap();
+        sponge.finish().unwrap();
+
+        // With a rate of 2, the third input is absorbed after a permutation.
+        // The squeeze permutes, then the fourth input lands in the first rate
+        // element without permuting, and the last squeeze permutes again.
+        let mut state = vec![GF::from(1), GF::from(2), tag(&io_pattern, b"test")];
+        permute(&mut state, &PARAMS).unwrap();
+        state[0] += GF::from(3);
+        permute(&mut state, &PARAMS).unwrap();
+        assert_eq!(first, vec![state[0]]);
+        state[0] += GF::from(4);
+        permute(&mut state, &PARAMS).unwrap();
+        assert_eq!(second, state[..2]);
+
+        // Independent implementation of the pseudocode of the specification
+        // in Python, over the same permutation.
+        let expected = felts_from_str::<GF>(&[
+            "12926602016995404780047559520316898928961965044245552138227844304307347278402",
+            "4764160994601046423708146321007023088258831119265079903152942245577483389495",
+            "7634570005139770239134381074295111325484504639429047457793642423076198839108",
+        ]);
+        assert_eq!([first, second].concat(), expected);
+    }
+
+    #[test]
+    fn test_deviating_calls() {
+        let io_pattern = [SpongeOp::Absorb(2), SpongeOp::Squeeze(1)];
+        let mut sponge = Sponge::<GF>::start(&io_pattern, b"", &PARAMS).unwrap();
+        assert_eq!(
+            sponge.squeeze(1),
+            Err(SpongeError::UnexpectedCall {
+                expected: Some(SpongeOp::Absorb(2)),
+                got: SpongeOp::Squeeze(1),
+            })
+        );
+        assert_eq!(
+            sponge.absorb(&[GF::from(1)]),
+            Err(SpongeError::UnexpectedCall {
+                expected: Some(SpongeOp::Absorb(2)),
+                got: SpongeOp::Absorb(1),
+            })
+        );
+        sponge.absorb(&[GF::from(1), GF::from(2)]).unwrap();
+        assert_eq!(
+            sponge.finish(),
+            Err(SpongeError::IncompletePattern { remaining: 1 })
+        );
+    }
+
+    #[test]
+    fn test_call_after_completion() {
+        let io_pattern = [SpongeOp::Absorb(1), SpongeOp::Squeeze(1)];
+        let mut sponge = Sponge::<GF>::start(&io_pattern, b"", &PARAMS).unwrap();
+        sponge.absorb(&[GF::from(1)]).unwrap();
+        sponge.squeeze(1).unwrap();
+        assert_eq!(
+            sponge.squeeze(1),
+            Err(SpongeError::UnexpectedCall {
+                expected: None,
+                got: SpongeOp::Squeeze(1),
+            })
+        );
+        sponge.finish().unwrap();
+    }
+}
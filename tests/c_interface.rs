@@ -0,0 +1,589 @@
+use ff::{Field, PrimeField};
+use poseidon::convert::{be_u8s_from_felts, felts_from_be_u8s, felts_from_u8s, u8s_from_felts};
+use poseidon::ffi::{
+    c_hash_with_parameters, c_precompile_required_gas, c_precompile_run, poseidon_get_params_info,
+    poseidon_params_count, poseidon_permute, poseidon_sponge_absorb, poseidon_sponge_clone,
+    poseidon_sponge_free, poseidon_sponge_new, poseidon_sponge_squeeze, poseidon_version,
+    PoseidonParamsInfo, POSEIDON_ERROR_INVALID_PARAMETERS, POSEIDON_ERROR_LENGTH,
+    POSEIDON_ERROR_NON_CANONICAL, POSEIDON_ERROR_NULL_POINTER, POSEIDON_ERROR_OUTPUT_TOO_SMALL,
+    POSEIDON_ERROR_UNKNOWN_PARAMS, POSEIDON_OK, POSEIDON_PARAMS_POSEIDON2_PALLAS,
+    POSEIDON_PARAMS_SW3,
+};
+use poseidon::parameters::{pallas, poseidon2, s128b, sw2, sw3, sw4, sw8, vesta};
+use std::ffi::CStr;
+
+macro_rules! test_c_interface {
+    ($name:ident, $gf:ty, $params:expr, $hash:path, $permute:path,
+        $c_hash:path, $c_hash_be:path, $c_hash_batch:path, $c_permute:path,
+        $c_output_size:path) => {
+        #[test]
+        fn $name() {
+            let output_size = $c_output_size();
+            assert_eq!(output_size, $params.output_size * 32);
+
+            let width = $params.rate + $params.capacity;
+            let felts: Vec<$gf> = (0..width as u64).map(|i| <$gf>::from(i + 7)).collect();
+            let input = u8s_from_felts(&felts[..$params.rate]);
+            let mut output = vec![0u8; output_size + 1];
+            let code = unsafe {
+                $c_hash(
+                    input.as_ptr(),
+                    input.len(),
+                    output.as_mut_ptr(),
+                    output.len(),
+                )
+            };
+            assert_eq!(code, POSEIDON_OK);
+            assert_eq!(
+                output[..output_size],
+                u8s_from_felts(&$hash(&felts[..$params.rate]))
+            );
+            assert_eq!(output[output_size], 0);
+
+            let input = be_u8s_from_felts(&felts[..$params.rate]);
+            let mut output = vec![0u8; output_size];
+            let code = unsafe {
+                $c_hash_be(
+                    input.as_ptr(),
+                    input.len(),
+                    output.as_mut_ptr(),
+                    output.len(),
+                )
+            };
+            assert_eq!(code, POSEIDON_OK);
+            assert_eq!(
+                felts_from_be_u8s::<$gf>(&output),
+                $hash(&felts[..$params.rate])
+            );
+            let input = u8s_from_felts(&felts[..$params.rate]);
+
+            let messages: Vec<$gf> = (0..2 * $params.rate as u64).map(<$gf>::from).collect();
+            let expected = [
+                $hash(&messages[..$params.rate]),
+                $hash(&messages[$params.rate..]),
+            ]
+            .concat();
+            let messages = u8s_from_felts(&messages);
+            let batch = |message_len, output: &mut [u8]| unsafe {
+                $c_hash_batch(
+                    messages.as_ptr(),
+                    messages.len(),
+                    message_len,
+                    output.as_mut_ptr(),
+                    output.len(),
+                )
+            };
+            let message_len = $params.rate * 32;
+            let mut output = vec![0u8; 2 * output_size];
+            assert_eq!(batch(message_len, &mut output), POSEIDON_OK);
+            assert_eq!(output, u8s_from_felts(&expected));
+            assert_eq!(
+                batch(message_len, &mut output[1..]),
+                POSEIDON_ERROR_OUTPUT_TOO_SMALL
+            );
+            assert_eq!(batch(0, &mut output), POSEIDON_ERROR_LENGTH);
+            assert_eq!(batch(message_len + 32, &mut output), POSEIDON_ERROR_LENGTH);
+
+            let mut state = u8s_from_felts(&felts);
+            let code = unsafe { $c_permute(state.as_mut_ptr(), state.len()) };
+            assert_eq!(code, POSEIDON_OK);
+            let mut expected = felts.clone();
+            $permute(&mut expected).unwrap();
+            assert_eq!(felts_from_u8s::<$gf>(&state), expected);
+
+            let hash = |input: &[u8], output: *mut u8, output_len| unsafe {
+                $c_hash(input.as_ptr(), input.len(), output, output_len)
+            };
+            let mut output = vec![0u8; output_size];
+            let output_ptr = output.as_mut_ptr();
+            assert_eq!(
+                hash(&input, core::ptr::null_mut(), output_size),
+                POSEIDON_ERROR_NULL_POINTER
+            );
+            assert_eq!(
+                unsafe { $c_hash(core::ptr::null(), 0, output_ptr, output_size) },
+                POSEIDON_ERROR_NULL_POINTER
+            );
+            assert_eq!(
+                hash(&input[1..], output_ptr, output_size),
+                POSEIDON_ERROR_LENGTH
+            );
+            assert_eq!(hash(&[], output_ptr, output_size), POSEIDON_ERROR_LENGTH);
+            let mut non_canonical = input.clone();
+            non_canonical[..32].fill(0xff);
+            assert_eq!(
+                hash(&non_canonical, output_ptr, output_size),
+                POSEIDON_ERROR_NON_CANONICAL
+            );
+            assert_eq!(
+                hash(&input, output_ptr, output_size - 1),
+                POSEIDON_ERROR_OUTPUT_TOO_SMALL
+            );
+            assert!(output.iter().all(|&byte| byte == 0));
+
+            let mut state = u8s_from_felts(&felts[1..]);
+            let code = unsafe { $c_permute(state.as_mut_ptr(), state.len()) };
+            assert_eq!(code, POSEIDON_ERROR_LENGTH);
+            let code = unsafe { $c_permute(core::ptr::null_mut(), 0) };
+            assert_eq!(code, POSEIDON_ERROR_NULL_POINTER);
+        }
+    };
+}
+
+test_c_interface!(
+    test_c_interface_s128b,
+    s128b::GF,
+    s128b::PARAMS,
+    poseidon::hash_s128b,
+    poseidon::permute_s128b,
+    poseidon::ffi::c_hash_s128b,
+    poseidon::ffi::c_hash_be_s128b,
+    poseidon::ffi::c_hash_batch_s128b,
+    poseidon::ffi::c_permute_s128b,
+    poseidon::ffi::c_output_size_s128b
+);
+test_c_interface!(
+    test_c_interface_sw2,
+    sw2::GF,
+    sw2::PARAMS,
+    poseidon::hash_sw2,
+    poseidon::permute_sw2,
+    poseidon::ffi::c_hash_sw2,
+    poseidon::ffi::c_hash_be_sw2,
+    poseidon::ffi::c_hash_batch_sw2,
+    poseidon::ffi::c_permute_sw2,
+    poseidon::ffi::c_output_size_sw2
+);
+test_c_interface!(
+    test_c_interface_sw3,
+    sw3::GF,
+    sw3::PARAMS,
+    poseidon::hash_sw3,
+    poseidon::permute_sw3,
+    poseidon::ffi::c_hash_sw3,
+    poseidon::ffi::c_hash_be_sw3,
+    poseidon::ffi::c_hash_batch_sw3,
+    poseidon::ffi::c_permute_sw3,
+    poseidon::ffi::c_output_size_sw3
+);
+test_c_interface!(
+    test_c_interface_sw4,
+    sw4::GF,
+    sw4::PARAMS,
+    poseidon::hash_sw4,
+    poseidon::permute_sw4,
+    poseidon::ffi::c_hash_sw4,
+    poseidon::ffi::c_hash_be_sw4,
+    poseidon::ffi::c_hash_batch_sw4,
+    poseidon::ffi::c_permute_sw4,
+    poseidon::ffi::c_output_size_sw4
+);
+test_c_interface!(
+    test_c_interface_sw8,
+    sw8::GF,
+    sw8::PARAMS,
+    poseidon::hash_sw8,
+    poseidon::permute_sw8,
+    poseidon::ffi::c_hash_sw8,
+    poseidon::ffi::c_hash_be_sw8,
+    poseidon::ffi::c_hash_batch_sw8,
+    poseidon::ffi::c_permute_sw8,
+    poseidon::ffi::c_output_size_sw8
+);
+test_c_interface!(
+    test_c_interface_pallas,
+    pallas::GF,
+    pallas::PARAMS,
+    poseidon::hash_pallas,
+    poseidon::permute_pallas,
+    poseidon::ffi::c_hash_pallas,
+    poseidon::ffi::c_hash_be_pallas,
+    poseidon::ffi::c_hash_batch_pallas,
+    poseidon::ffi::c_permute_pallas,
+    poseidon::ffi::c_output_size_pallas
+);
+test_c_interface!(
+    test_c_interface_vesta,
+    vesta::GF,
+    vesta::PARAMS,
+    poseidon::hash_vesta,
+    poseidon::permute_vesta,
+    poseidon::ffi::c_hash_vesta,
+    poseidon::ffi::c_hash_be_vesta,
+    poseidon::ffi::c_hash_batch_vesta,
+    poseidon::ffi::c_permute_vesta,
+    poseidon::ffi::c_output_size_vesta
+);
+test_c_interface!(
+    test_c_interface_poseidon2_pallas,
+    pallas::GF,
+    poseidon::parameters::poseidon2::pallas::PARAMS,
+    poseidon::hash_poseidon2_pallas,
+    poseidon::permute_poseidon2_pallas,
+    poseidon::ffi::c_hash_poseidon2_pallas,
+    poseidon::ffi::c_hash_be_poseidon2_pallas,
+    poseidon::ffi::c_hash_batch_poseidon2_pallas,
+    poseidon::ffi::c_permute_poseidon2_pallas,
+    poseidon::ffi::c_output_size_poseidon2_pallas
+);
+test_c_interface!(
+    test_c_interface_poseidon2_vesta,
+    vesta::GF,
+    poseidon::parameters::poseidon2::vesta::PARAMS,
+    poseidon::hash_poseidon2_vesta,
+    poseidon::permute_poseidon2_vesta,
+    poseidon::ffi::c_hash_poseidon2_vesta,
+    poseidon::ffi::c_hash_be_poseidon2_vesta,
+    poseidon::ffi::c_hash_batch_poseidon2_vesta,
+    poseidon::ffi::c_permute_poseidon2_vesta,
+    poseidon::ffi::c_output_size_poseidon2_vesta
+);
+test_c_interface!(
+    test_c_interface_poseidon2_stark252,
+    sw3::GF,
+    poseidon::parameters::poseidon2::stark252::PARAMS,
+    poseidon::hash_poseidon2_stark252,
+    poseidon::permute_poseidon2_stark252,
+    poseidon::ffi::c_hash_poseidon2_stark252,
+    poseidon::ffi::c_hash_be_poseidon2_stark252,
+    poseidon::ffi::c_hash_batch_poseidon2_stark252,
+    poseidon::ffi::c_permute_poseidon2_stark252,
+    poseidon::ffi::c_output_size_poseidon2_stark252
+);
+
+#[test]
+fn test_c_sponge() {
+    let felts: Vec<sw3::GF> = (0..9u64).map(|i| sw3::GF::from(i + 7)).collect();
+    let input = u8s_from_felts(&felts);
+    let expected = u8s_from_felts(&poseidon::hash_sw3(&felts));
+
+    let sponge = poseidon_sponge_new(POSEIDON_PARAMS_SW3);
+    assert!(!sponge.is_null());
+    let mut output = vec![0u8; expected.len()];
+    unsafe {
+        for chunk in input.chunks(64) {
+            let code = poseidon_sponge_absorb(sponge, chunk.as_ptr(), chunk.len());
+            assert_eq!(code, POSEIDON_OK);
+        }
+        let copy = poseidon_sponge_clone(sponge);
+        let code = poseidon_sponge_squeeze(sponge, output.as_mut_ptr(), output.len());
+        assert_eq!(code, POSEIDON_OK);
+        assert_eq!(output, expected);
+        output.fill(0);
+        let code = poseidon_sponge_squeeze(copy, output.as_mut_ptr(), output.len());
+        assert_eq!(code, POSEIDON_OK);
+        assert_eq!(output, expected);
+        poseidon_sponge_free(copy);
+
+        let code = poseidon_sponge_absorb(sponge, input.as_ptr(), 31);
+        assert_eq!(code, POSEIDON_ERROR_LENGTH);
+        let non_canonical = [0xffu8; 64];
+        let code = poseidon_sponge_absorb(sponge, non_canonical.as_ptr(), 64);
+        assert_eq!(code, POSEIDON_ERROR_NON_CANONICAL);
+        let code = poseidon_sponge_squeeze(sponge, output.as_mut_ptr(), 33);
+        assert_eq!(code, POSEIDON_ERROR_LENGTH);
+        let code = poseidon_sponge_absorb(core::ptr::null_mut(), input.as_ptr(), 32);
+        assert_eq!(code, POSEIDON_ERROR_NULL_POINTER);
+        assert!(poseidon_sponge_clone(core::ptr::null()).is_null());
+        poseidon_sponge_free(sponge);
+        poseidon_sponge_free(core::ptr::null_mut());
+    }
+    assert!(poseidon_sponge_new(u32::MAX).is_null());
+}
+
+#[test]
+fn test_c_params() {
+    let expected = [
+        (
+            "s128b",
+            s128b::PARAMS.rate,
+            s128b::PARAMS.capacity,
+            s128b::PARAMS.output_size,
+        ),
+        (
+            "sw2",
+            sw2::PARAMS.rate,
+            sw2::PARAMS.capacity,
+            sw2::PARAMS.output_size,
+        ),
+        (
+            "sw3",
+            sw3::PARAMS.rate,
+            sw3::PARAMS.capacity,
+            sw3::PARAMS.output_size,
+        ),
+        (
+            "sw4",
+            sw4::PARAMS.rate,
+            sw4::PARAMS.capacity,
+            sw4::PARAMS.output_size,
+        ),
+        (
+            "sw8",
+            sw8::PARAMS.rate,
+            sw8::PARAMS.capacity,
+            sw8::PARAMS.output_size,
+        ),
+        (
+            "pallas",
+            pallas::PARAMS.rate,
+            pallas::PARAMS.capacity,
+            pallas::PARAMS.output_size,
+        ),
+        (
+            "vesta",
+            vesta::PARAMS.rate,
+            vesta::PARAMS.capacity,
+            vesta::PARAMS.output_size,
+        ),
+        (
+            "poseidon2_pallas",
+            poseidon2::pallas::PARAMS.rate,
+            poseidon2::pallas::PARAMS.capacity,
+            poseidon2::pallas::PARAMS.output_size,
+        ),
+        (
+            "poseidon2_vesta",
+            poseidon2::vesta::PARAMS.rate,
+            poseidon2::vesta::PARAMS.capacity,
+            poseidon2::vesta::PARAMS.output_size,
+        ),
+        (
+            "poseidon2_stark252",
+            poseidon2::stark252::PARAMS.rate,
+            poseidon2::stark252::PARAMS.capacity,
+            poseidon2::stark252::PARAMS.output_size,
+        ),
+    ];
+    assert_eq!(poseidon_params_count() as usize, expected.len());
+    for (id, &(name, rate, capacity, output_size)) in expected.iter().enumerate() {
+        let mut info = std::mem::MaybeUninit::<PoseidonParamsInfo>::uninit();
+        let code = unsafe { poseidon_get_params_info(id as u32, info.as_mut_ptr()) };
+        assert_eq!(code, POSEIDON_OK);
+        let info = unsafe { info.assume_init() };
+        assert_eq!(unsafe { CStr::from_ptr(info.name) }.to_str(), Ok(name));
+        assert_eq!(
+            (info.rate, info.capacity, info.output_size, info.field_size),
+            (rate, capacity, output_size, 32)
+        );
+
+        let mut state = vec![0u8; (rate + capacity) * 32];
+        let code = unsafe { poseidon_permute(id as u32, state.as_mut_ptr(), state.len()) };
+        assert_eq!(code, POSEIDON_OK);
+        assert_ne!(state, vec![0u8; state.len()]);
+        let code = unsafe { poseidon_permute(id as u32, state.as_mut_ptr(), 32) };
+        assert_eq!(code, POSEIDON_ERROR_LENGTH);
+    }
+
+    let felts: Vec<sw3::GF> = (0..4u64).map(sw3::GF::from).collect();
+    let mut expected = felts.clone();
+    poseidon::permute_sw3(&mut expected).unwrap();
+    let mut state = u8s_from_felts(&felts);
+    let code = unsafe { poseidon_permute(POSEIDON_PARAMS_SW3, state.as_mut_ptr(), state.len()) };
+    assert_eq!(code, POSEIDON_OK);
+    assert_eq!(state, u8s_from_felts(&expected));
+
+    let felts: Vec<pallas::GF> = (0..poseidon2::pallas::PARAMS.rate as u64 + 1)
+        .map(pallas::GF::from)
+        .collect();
+    let mut expected = felts.clone();
+    poseidon::permute_poseidon2_pallas(&mut expected).unwrap();
+    let mut state = u8s_from_felts(&felts);
+    let code = unsafe {
+        poseidon_permute(
+            POSEIDON_PARAMS_POSEIDON2_PALLAS,
+            state.as_mut_ptr(),
+            state.len(),
+        )
+    };
+    assert_eq!(code, POSEIDON_OK);
+    assert_eq!(state, u8s_from_felts(&expected));
+    assert!(poseidon_sponge_new(POSEIDON_PARAMS_POSEIDON2_PALLAS).is_null());
+
+    let mut info = std::mem::MaybeUninit::<PoseidonParamsInfo>::uninit();
+    let code = unsafe { poseidon_get_params_info(u32::MAX, info.as_mut_ptr()) };
+    assert_eq!(code, POSEIDON_ERROR_UNKNOWN_PARAMS);
+    let code = unsafe { poseidon_get_params_info(0, core::ptr::null_mut()) };
+    assert_eq!(code, POSEIDON_ERROR_NULL_POINTER);
+    let code = unsafe { poseidon_permute(u32::MAX, state.as_mut_ptr(), state.len()) };
+    assert_eq!(code, POSEIDON_ERROR_UNKNOWN_PARAMS);
+    let code = unsafe { poseidon_permute(0, core::ptr::null_mut(), 0) };
+    assert_eq!(code, POSEIDON_ERROR_NULL_POINTER);
+
+    let version = unsafe { CStr::from_ptr(poseidon_version()) };
+    assert_eq!(version.to_str(), Ok(env!("CARGO_PKG_VERSION")));
+}
+
+/// Returns the 32-byte big-endian word of an element.
+fn be_word<F: PrimeField>(felt: &F) -> Vec<u8> {
+    let mut bytes = felt.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    bytes
+}
+
+#[test]
+fn test_c_hash_with_parameters() {
+    let params = s128b::PARAMS;
+    let mut parameters = be_word(&-s128b::GF::ONE);
+    *parameters.last_mut().unwrap() += 1;
+    for value in [
+        params.power as u64,
+        params.rate as u64,
+        params.capacity as u64,
+        params.output_size as u64,
+        params.n_full_rounds as u64,
+        params.n_partial_rounds as u64,
+    ] {
+        parameters.extend(be_word(&s128b::GF::from(value)));
+    }
+    for constant in params.mds_matrix.iter().chain(params.round_constants) {
+        parameters.extend(be_word(&s128b::GF::from_str_vartime(constant).unwrap()));
+    }
+    let felts = [s128b::GF::from(7), s128b::GF::from(54)];
+    let input: Vec<u8> = felts.iter().flat_map(be_word).collect();
+    let expected: Vec<u8> = poseidon::hash_s128b(&felts)
+        .iter()
+        .flat_map(be_word)
+        .collect();
+
+    let hash = |parameters: &[u8], input: &[u8], output: &mut [u8], written: &mut usize| unsafe {
+        c_hash_with_parameters(
+            parameters.as_ptr(),
+            parameters.len(),
+            input.as_ptr(),
+            input.len(),
+            output.as_mut_ptr(),
+            output.len(),
+            written,
+        )
+    };
+    let mut output = vec![0u8; 64];
+    let mut written = 0;
+    assert_eq!(
+        hash(&parameters, &input, &mut output, &mut written),
+        POSEIDON_OK
+    );
+    assert_eq!(output[..written], expected);
+
+    assert_eq!(
+        hash(&parameters, &input[..32], &mut output, &mut written),
+        POSEIDON_ERROR_LENGTH
+    );
+    assert_eq!(
+        hash(&parameters, &input, &mut output[..31], &mut written),
+        POSEIDON_ERROR_OUTPUT_TOO_SMALL
+    );
+    assert_eq!(
+        hash(&parameters, &parameters[..64], &mut output, &mut written),
+        POSEIDON_ERROR_NON_CANONICAL
+    );
+    let mut singular = parameters.clone();
+    singular[7 * 32..10 * 32].fill(0);
+    assert_eq!(
+        hash(&singular, &input, &mut output, &mut written),
+        POSEIDON_ERROR_INVALID_PARAMETERS
+    );
+}
+
+#[test]
+fn test_c_precompile_errors() {
+    let mut gas = 0u64;
+    let input = [0u8; 33];
+    let code = unsafe { c_precompile_required_gas(input.as_ptr(), input.len(), &mut gas) };
+    assert_eq!(code, POSEIDON_ERROR_LENGTH);
+    let code = unsafe { c_precompile_required_gas(input.as_ptr(), 32, core::ptr::null_mut()) };
+    assert_eq!(code, POSEIDON_ERROR_NULL_POINTER);
+
+    // Modulus 0.
+    let input = [0u8; 32 * 7];
+    let mut output = [0u8; 32];
+    let mut written = 0usize;
+    let code = unsafe {
+        c_precompile_run(
+            input.as_ptr(),
+            input.len(),
+            u64::MAX,
+            output.as_mut_ptr(),
+            output.len(),
+            &mut written,
+            &mut gas,
+        )
+    };
+    assert_eq!(code, POSEIDON_ERROR_INVALID_PARAMETERS);
+    assert_eq!((written, gas), (0, 0));
+}
+
+/// Returns the names of the C functions exported by the crate: functions
+/// declared `extern "C"` and those generated by `c_interface!`.
+fn exported_functions(source: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    for (i, _) in source.match_indices("extern \"C\" fn ") {
+        let rest = &source[i + "extern \"C\" fn ".len()..];
+        let name = &rest[..rest.find('(').unwrap()];
+        if !name.starts_with('$') {
+            names.push(name.to_string());
+        }
+    }
+    for (i, _) in source.match_indices("\nc_interface!(") {
+        let invocation = &source[i..i + source[i..].find(");").unwrap()];
+        let generated = &invocation[invocation.find("=>").unwrap() + 2..];
+        names.extend(generated.split(',').map(|name| name.trim().to_string()));
+    }
+    names.sort();
+    names
+}
+
+/// Returns the names and values of the `POSEIDON_*` constants of the crate.
+fn exported_constants(source: &str) -> Vec<(String, i64)> {
+    let mut constants: Vec<(String, i64)> = source
+        .lines()
+        .filter_map(|line| line.strip_prefix("pub const POSEIDON_"))
+        .map(|line| {
+            let (name, rest) = line.split_once(": ").unwrap();
+            let value = rest.split_once(" = ").unwrap().1;
+            let value = value.trim_end_matches(';').parse().unwrap();
+            (format!("POSEIDON_{name}"), value)
+        })
+        .collect();
+    constants.sort();
+    constants
+}
+
+/// Returns the names and values of the `POSEIDON_*` constants defined in a C
+/// header.
+fn defined_constants(header: &str) -> Vec<(String, i64)> {
+    let mut constants: Vec<(String, i64)> = header
+        .lines()
+        .filter_map(|line| line.strip_prefix("#define POSEIDON_"))
+        .filter_map(|line| line.split_once(' '))
+        .map(|(name, value)| (format!("POSEIDON_{name}"), value.parse().unwrap()))
+        .collect();
+    constants.sort();
+    constants
+}
+
+/// Returns the names of the functions declared in a C header.
+fn declared_functions(header: &str) -> Vec<String> {
+    let mut names: Vec<String> = header
+        .lines()
+        .filter(|line| line.ends_with(");") && !line.starts_with(' '))
+        .map(|line| {
+            let name = &line[..line.find('(').unwrap()];
+            name.rsplit([' ', '*']).next().unwrap().to_string()
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[test]
+fn test_header() {
+    let source = include_str!("../src/ffi.rs");
+    let header = include_str!("../include/poseidon.h");
+    let exported = exported_functions(source);
+    assert!(exported.contains(&"c_hash_vesta".to_string()));
+    assert_eq!(declared_functions(header), exported);
+    let constants = exported_constants(source);
+    assert!(constants.contains(&("POSEIDON_OK".to_string(), 0)));
+    assert_eq!(defined_constants(header), constants);
+}
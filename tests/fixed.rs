@@ -0,0 +1,110 @@
+use poseidon::convert::felts_from_str;
+use poseidon::parameters::{pallas, poseidon2, s128b, sw2, sw3, sw4, sw8, vesta};
+use poseidon::{hash, permute, HashError};
+
+macro_rules! test_fixed {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            use $params::{GF, PARAMS, PERMUTATION, WIDTH};
+            assert_eq!(
+                $params::MDS_MATRIX[..],
+                felts_from_str::<GF>(PARAMS.mds_matrix)[..]
+            );
+            assert_eq!(
+                $params::ROUND_CONSTANTS[..],
+                felts_from_str::<GF>(PARAMS.round_constants)[..]
+            );
+
+            let mut state = [GF::from(0); WIDTH];
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = GF::from(i as u64 * 7919 + 1);
+            }
+            let mut expected = state.to_vec();
+            PERMUTATION.permute(&mut state);
+            permute(&mut expected, &PARAMS).unwrap();
+            assert_eq!(state[..], expected[..]);
+
+            let inputs: Vec<GF> = (0..2 * PARAMS.rate as u64).map(GF::from).collect();
+            let mut output = vec![GF::from(0); PARAMS.output_size];
+            PERMUTATION.hash(&inputs, &mut output).unwrap();
+            assert_eq!(output, hash(&inputs, &PARAMS).unwrap());
+            assert_eq!($params::hash(&inputs).unwrap()[..], output[..]);
+        }
+    };
+}
+
+macro_rules! test_fixed_poseidon2 {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            use poseidon2::$params::{GF, PARAMS, PERMUTATION, WIDTH};
+            assert_eq!(
+                poseidon2::$params::EXTERNAL_MATRIX[..],
+                felts_from_str::<GF>(PARAMS.external_matrix)[..]
+            );
+            assert_eq!(
+                poseidon2::$params::INTERNAL_DIAGONAL[..],
+                felts_from_str::<GF>(PARAMS.internal_diagonal)[..]
+            );
+            assert_eq!(
+                poseidon2::$params::ROUND_CONSTANTS[..],
+                felts_from_str::<GF>(PARAMS.round_constants)[..]
+            );
+
+            let mut state = [GF::from(0); WIDTH];
+            for (i, x) in state.iter_mut().enumerate() {
+                *x = GF::from(i as u64 * 7919 + 1);
+            }
+            let mut expected = state.to_vec();
+            PERMUTATION.permute(&mut state);
+            poseidon::poseidon2::permute(&mut expected, &PARAMS).unwrap();
+            assert_eq!(state[..], expected[..]);
+
+            let inputs: Vec<GF> = (0..2 * PARAMS.rate as u64).map(GF::from).collect();
+            let output = poseidon2::$params::hash(&inputs).unwrap();
+            assert_eq!(
+                output[..],
+                poseidon::poseidon2::hash(&inputs, &PARAMS).unwrap()[..]
+            );
+            assert_eq!(
+                poseidon2::$params::hash(&inputs[1..]),
+                Err(HashError::InputLength {
+                    length: 2 * PARAMS.rate - 1,
+                    rate: PARAMS.rate
+                })
+            );
+        }
+    };
+}
+
+test_fixed!(test_fixed_s128b, s128b);
+test_fixed!(test_fixed_sw2, sw2);
+test_fixed!(test_fixed_sw3, sw3);
+test_fixed!(test_fixed_sw4, sw4);
+test_fixed!(test_fixed_sw8, sw8);
+test_fixed!(test_fixed_pallas, pallas);
+test_fixed!(test_fixed_vesta, vesta);
+test_fixed_poseidon2!(test_fixed_poseidon2_pallas, pallas);
+test_fixed_poseidon2!(test_fixed_poseidon2_vesta, vesta);
+test_fixed_poseidon2!(test_fixed_poseidon2_stark252, stark252);
+
+#[test]
+fn test_permute_wrong_width() {
+    let mut state = [s128b::GF::from(0); 2];
+    assert_eq!(
+        poseidon::permute_s128b(&mut state),
+        Err(HashError::StateLength {
+            length: 2,
+            width: s128b::WIDTH
+        })
+    );
+    let mut state = [pallas::GF::from(0); 4];
+    assert_eq!(
+        poseidon::permute_poseidon2_pallas(&mut state),
+        Err(HashError::StateLength {
+            length: 4,
+            width: poseidon2::pallas::WIDTH
+        })
+    );
+}
@@ -13,10 +13,13 @@ import (
     "unsafe"
 )
 
-func hash(input []byte) ([]byte) {
-    output := make([]byte, 32)
-    count := C.c_hash_s128b((*C.uint8_t)(unsafe.Pointer(&input[0])), C.size_t(len(input)), (*C.uint8_t)(unsafe.Pointer(&output[0])), C.size_t(len(output)))
-    return output[:count]
+func hash(input []byte) ([]byte, error) {
+    output := make([]byte, C.c_output_size_s128b())
+    code := C.c_hash_s128b((*C.uint8_t)(unsafe.Pointer(&input[0])), C.size_t(len(input)), (*C.uint8_t)(unsafe.Pointer(&output[0])), C.size_t(len(output)))
+    if code != C.POSEIDON_OK {
+        return nil, fmt.Errorf("error code %d", code)
+    }
+    return output, nil
 }
 
 func main() {
@@ -36,8 +39,10 @@ func main() {
         83, 235, 187, 118, 177, 146, 208, 154,
         187, 33, 119, 100, 131, 7, 112, 24,
     }
-    output := hash(input)
-    if bytes.Equal(output, expected) {
+    output, err := hash(input)
+    if err != nil {
+	fmt.Printf("[Failed ] s128b: %v\n", err)
+    } else if bytes.Equal(output, expected) {
         fmt.Printf("[Success] s128b\n")
     } else {
 	fmt.Printf("[Failed ] s128b: Unexpected elements: %v\n", output)
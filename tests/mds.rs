@@ -0,0 +1,24 @@
+use poseidon::mds::{algorithm_1, algorithm_2, algorithm_3};
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+
+macro_rules! test_mds {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            let prepared = $params::prepared();
+            let width = prepared.rate() + prepared.capacity();
+            let mds_matrix = prepared.mds_matrix();
+            assert!(algorithm_1(mds_matrix, width));
+            assert!(algorithm_2(mds_matrix, width));
+            assert!(algorithm_3(mds_matrix, width));
+        }
+    };
+}
+
+test_mds!(test_mds_s128b, s128b);
+test_mds!(test_mds_sw2, sw2);
+test_mds!(test_mds_sw3, sw3);
+test_mds!(test_mds_sw4, sw4);
+test_mds!(test_mds_sw8, sw8);
+test_mds!(test_mds_pallas, pallas);
+test_mds!(test_mds_vesta, vesta);
@@ -0,0 +1,29 @@
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+use poseidon::{permute, permute_optimized};
+
+macro_rules! test_optimized {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            use $params::GF;
+            let width = $params::PARAMS.rate + $params::PARAMS.capacity;
+            for seed in 0..4u64 {
+                let mut state: Vec<GF> = (0..width as u64)
+                    .map(|i| GF::from(seed * 1_000_003 + i * i * 7919 + 1))
+                    .collect();
+                let mut expected = state.clone();
+                permute_optimized(&mut state, $params::optimized()).unwrap();
+                permute(&mut expected, $params::prepared()).unwrap();
+                assert_eq!(state, expected);
+            }
+        }
+    };
+}
+
+test_optimized!(test_optimized_s128b, s128b);
+test_optimized!(test_optimized_sw2, sw2);
+test_optimized!(test_optimized_sw3, sw3);
+test_optimized!(test_optimized_sw4, sw4);
+test_optimized!(test_optimized_sw8, sw8);
+test_optimized!(test_optimized_pallas, pallas);
+test_optimized!(test_optimized_vesta, vesta);
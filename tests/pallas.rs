@@ -1,20 +1,36 @@
 use poseidon::convert::felts_from_str;
 use poseidon::hash_pallas as hash;
+use poseidon::hash_var_len_pallas as hash_var_len;
 use poseidon::parameters::pallas::GF;
 
 #[test]
 fn test_hash_simple() {
+    // All 55 full rounds, checked against an independent implementation of
+    // the round sequence.
     let input = ["0", "0"];
     let input = felts_from_str::<GF>(&input);
     let expected = [
-        "15203699030908700109218914175527927806302520478319440272484330643709021075093",
-        "25101873011696671024407923684611714012645440013636881009339041864418865390740",
+        "2466288369849047915087280696269316273005828955535825463922995155458172612141",
+        "25956921645870954193130807018083471992431814913719513076593589718863979880200",
     ];
     let expected = felts_from_str::<GF>(&expected);
     let output = hash(&input);
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "26201285143449299243944309367143069944455019876927399607525714607272893456277",
+        "18815209915752226952019617567546058993717526381639909805755902675364303804446",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
@@ -0,0 +1,60 @@
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+
+macro_rules! test_permute_matches_hash {
+    ($name:ident, $params:ident, $permute:path, $hash:path) => {
+        #[test]
+        fn $name() {
+            use $params::{GF, PARAMS};
+            let input: Vec<GF> = (0..PARAMS.rate as u64)
+                .map(|i| GF::from(7 * i + 1))
+                .collect();
+            let mut state = input.clone();
+            state.resize(PARAMS.rate + PARAMS.capacity, GF::from(0));
+            $permute(&mut state).unwrap();
+            assert_eq!(state[..PARAMS.output_size], $hash(&input)[..]);
+        }
+    };
+}
+
+test_permute_matches_hash!(
+    test_permute_matches_hash_s128b,
+    s128b,
+    poseidon::permute_s128b,
+    poseidon::hash_s128b
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_sw2,
+    sw2,
+    poseidon::permute_sw2,
+    poseidon::hash_sw2
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_sw3,
+    sw3,
+    poseidon::permute_sw3,
+    poseidon::hash_sw3
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_sw4,
+    sw4,
+    poseidon::permute_sw4,
+    poseidon::hash_sw4
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_sw8,
+    sw8,
+    poseidon::permute_sw8,
+    poseidon::hash_sw8
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_pallas,
+    pallas,
+    poseidon::permute_pallas,
+    poseidon::hash_pallas
+);
+test_permute_matches_hash!(
+    test_permute_matches_hash_vesta,
+    vesta,
+    poseidon::permute_vesta,
+    poseidon::hash_vesta
+);
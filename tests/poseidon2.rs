@@ -0,0 +1,93 @@
+use poseidon::convert::felts_from_str;
+use poseidon::parameters::poseidon2::{pallas, stark252, vesta};
+use poseidon::{hash_poseidon2_pallas, hash_poseidon2_stark252, hash_poseidon2_vesta};
+use poseidon::{permute_poseidon2_pallas, permute_poseidon2_stark252, permute_poseidon2_vesta};
+
+// Known answers of the Poseidon2 reference implementation, on input [0, 1, 2].
+
+#[test]
+fn test_permute_pallas() {
+    use pallas::GF;
+    let mut state = vec![GF::from(0), GF::from(1), GF::from(2)];
+    permute_poseidon2_pallas(&mut state).unwrap();
+    let expected = [
+        "12034580478475899756768852307737011850845987783813919900518943507286591966586",
+        "12793588015935436972406883162492490643371701127091833715009315828048670032380",
+        "3445110498342580915003896963218627245598739827334005084754590007820223034369",
+    ];
+    assert_eq!(state, felts_from_str::<GF>(&expected));
+}
+
+#[test]
+fn test_permute_vesta() {
+    use vesta::GF;
+    let mut state = vec![GF::from(0), GF::from(1), GF::from(2)];
+    permute_poseidon2_vesta(&mut state).unwrap();
+    let expected = [
+        "17242300747239067162598679661883726010595470725927247522813204773213848872850",
+        "20110601776785301845784077236520917651993463881458952614501114449858670413423",
+        "17249884929888930719727146739709554376568891774225041385253561222176908575167",
+    ];
+    assert_eq!(state, felts_from_str::<GF>(&expected));
+}
+
+// The reference implementation has no Stark252 instance. Known answer on
+// input [0, 1, 2] of an independent implementation of its permutation and
+// parameter script, guarding the constants against changes.
+
+#[test]
+fn test_permute_stark252() {
+    use stark252::GF;
+    let mut state = vec![GF::from(0), GF::from(1), GF::from(2)];
+    permute_poseidon2_stark252(&mut state).unwrap();
+    let expected = [
+        "2636513244757622239477671532006709592893292798319452571875667726569061209941",
+        "3053597153234500627260178284222583714873883608991212969292943598348675525275",
+        "3310116417005377841962502657772976450982263931205216567004330596558028302674",
+    ];
+    assert_eq!(state, felts_from_str::<GF>(&expected));
+}
+
+#[test]
+fn test_round_constants_stark252() {
+    use stark252::{GF, PARAMS};
+    let constants = poseidon::grain::poseidon2_round_constants::<GF>(
+        PARAMS.rate + PARAMS.capacity,
+        PARAMS.n_full_rounds,
+        PARAMS.n_partial_rounds,
+    );
+    assert_eq!(constants, felts_from_str::<GF>(PARAMS.round_constants));
+}
+
+#[test]
+fn test_hash_pallas() {
+    use pallas::GF;
+    let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+    permute_poseidon2_pallas(&mut state).unwrap();
+    assert_eq!(
+        hash_poseidon2_pallas(&[GF::from(7), GF::from(98)]),
+        vec![state[0]]
+    );
+}
+
+#[test]
+fn test_hash_vesta() {
+    use vesta::GF;
+    let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+    permute_poseidon2_vesta(&mut state).unwrap();
+    assert_eq!(
+        hash_poseidon2_vesta(&[GF::from(7), GF::from(98)]),
+        vec![state[0]]
+    );
+}
+
+#[test]
+fn test_hash_stark252() {
+    use stark252::GF;
+    let mut state = vec![GF::from(7), GF::from(98), GF::from(0)];
+    permute_poseidon2_stark252(&mut state).unwrap();
+    assert_eq!(
+        hash_poseidon2_stark252(&[GF::from(7), GF::from(98)]),
+        vec![state[0]]
+    );
+}
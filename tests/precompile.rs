@@ -0,0 +1,64 @@
+use ff::{Field, PrimeField};
+use poseidon::parameters::{s128b, sw2, sw3, sw4, sw8, Parameters};
+use poseidon::precompile::{required_gas, run};
+
+/// Returns the big-endian word of an element of a little-endian field.
+fn be_word<F: PrimeField>(felt: &F) -> Vec<u8> {
+    let mut bytes = felt.to_repr().as_ref().to_vec();
+    bytes.reverse();
+    bytes
+}
+
+fn small_word(value: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; 24];
+    bytes.extend_from_slice(&(value as u64).to_be_bytes());
+    bytes
+}
+
+fn encode<F: PrimeField>(params: &Parameters, inputs: &[F]) -> Vec<u8> {
+    // p = (p - 1) + 1, p being odd.
+    let mut input = be_word(&-F::ONE);
+    *input.last_mut().unwrap() += 1;
+    for value in [
+        params.power as usize,
+        params.rate,
+        params.capacity,
+        params.output_size,
+        params.n_full_rounds,
+        params.n_partial_rounds,
+    ] {
+        input.extend(small_word(value));
+    }
+    for constant in params.mds_matrix.iter().chain(params.round_constants) {
+        input.extend(be_word(&F::from_str_vartime(constant).unwrap()));
+    }
+    for felt in inputs {
+        input.extend(be_word(felt));
+    }
+    input
+}
+
+macro_rules! test_precompile {
+    ($name:ident, $params:ident, $hash:path) => {
+        #[test]
+        fn $name() {
+            type GF = $params::GF;
+            let inputs: Vec<GF> = (0..2 * $params::PARAMS.rate)
+                .map(|i| GF::from(i as u64 + 7))
+                .collect();
+            let input = encode(&$params::PARAMS, &inputs);
+            let (output, gas) = run(&input, u64::MAX).unwrap();
+            let expected: Vec<u8> = $hash(&inputs).iter().flat_map(be_word).collect();
+            assert_eq!(output, expected);
+            assert_eq!(required_gas(&input), Ok(gas));
+            assert!(gas > 0);
+            assert_ne!(expected, be_word(&GF::ZERO));
+        }
+    };
+}
+
+test_precompile!(test_precompile_s128b, s128b, poseidon::hash_s128b);
+test_precompile!(test_precompile_sw2, sw2, poseidon::hash_sw2);
+test_precompile!(test_precompile_sw3, sw3, poseidon::hash_sw3);
+test_precompile!(test_precompile_sw4, sw4, poseidon::hash_sw4);
+test_precompile!(test_precompile_sw8, sw8, poseidon::hash_sw8);
@@ -1,9 +1,11 @@
 use ff::{Field, PrimeField};
 use poseidon;
 use poseidon::convert::{
-    felts_from_str, felts_from_u8s, scalar_from_u64s, scalar_from_u8s, u8s_from_felts,
+    be_u8s_from_felts, felts_from_be_u8s, felts_from_str, felts_from_u8s, scalar_from_be_u8s,
+    scalar_from_u64s, scalar_from_u8s, u8s_from_felts,
 };
 use poseidon::hash_s128b as hash;
+use poseidon::hash_var_len_s128b as hash_var_len;
 use poseidon::parameters::s128b::GF;
 
 #[test]
@@ -44,6 +46,23 @@ fn test_ff_conversion() {
     assert_eq!(a_felts, b_felts);
 }
 
+#[test]
+fn test_ff_be_conversion() {
+    // abi.encode(uint256[]) data of [7, 343], without offset and length.
+    let mut be_u8s = [0u8; 64];
+    be_u8s[31] = 7;
+    be_u8s[62] = 1;
+    be_u8s[63] = 87;
+    assert_eq!(scalar_from_be_u8s::<GF>(&be_u8s[..32]), GF::from(7));
+    let felts = felts_from_be_u8s::<GF>(&be_u8s);
+    assert_eq!(felts, vec![GF::from(7), GF::from(343)]);
+    assert_eq!(be_u8s_from_felts(&felts), be_u8s);
+    let mut le_u8s = u8s_from_felts(&felts);
+    le_u8s[..32].reverse();
+    le_u8s[32..].reverse();
+    assert_eq!(le_u8s, be_u8s);
+}
+
 #[test]
 fn test_hash_simple() {
     let input = ["7", "98"];
@@ -71,6 +90,29 @@ fn test_hash_double() {
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected =
+        ["10379529914210266847129054097127715627591325426845540723695398925109836371652"];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
+#[test]
+fn test_hash_var_len_reference() {
+    // Computed with tests/reference/s128b.py, written from the Poseidon paper.
+    let input = ["7", "54", "3"];
+    let input = felts_from_str::<GF>(&input);
+    let expected =
+        ["11890080940142088196028105749968610186265484015082185300984702590666015961701"];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
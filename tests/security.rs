@@ -0,0 +1,44 @@
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+use poseidon::security::{report, RoundNumbers};
+
+macro_rules! test_security {
+    ($name:ident, $params:ident, $full_margin:expr, $partial_margin:expr, $recommended:expr) => {
+        #[test]
+        fn $name() {
+            let report = report::<$params::GF>(&$params::PARAMS, 128);
+            assert!(report.secure);
+            assert_eq!(report.full_rounds_margin, Some($full_margin));
+            assert_eq!(report.partial_rounds_margin, Some($partial_margin));
+            let (n_full_rounds, n_partial_rounds) = $recommended;
+            let recommended = RoundNumbers {
+                n_full_rounds,
+                n_partial_rounds,
+            };
+            assert_eq!(report.recommended, Some(recommended));
+        }
+    };
+}
+
+// Margins at 128 bits of security, in full rounds with the partial rounds of
+// the set, and in partial rounds with its full rounds.
+test_security!(test_security_s128b, s128b, 2, 8, (8, 83));
+test_security!(test_security_sw2, sw2, 2, 8, (8, 83));
+test_security!(test_security_sw3, sw3, 2, 8, (8, 84));
+test_security!(test_security_sw4, sw4, 2, 8, (8, 84));
+test_security!(test_security_sw8, sw8, 2, 8, (8, 84));
+test_security!(test_security_pallas, pallas, 7, 0, (8, 46));
+test_security!(test_security_vesta, vesta, 7, 0, (8, 46));
+
+#[test]
+fn test_security_narrow_width() {
+    let params = poseidon::parameters::Parameters {
+        rate: 1,
+        capacity: 0,
+        ..s128b::PARAMS
+    };
+    let report = report::<s128b::GF>(&params, 128);
+    assert!(!report.secure);
+    assert_eq!(report.full_rounds_margin, None);
+    assert_eq!(report.partial_rounds_margin, None);
+    assert_eq!(report.recommended, None);
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "serde")]
+
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+use poseidon::parameters::{ParametersError, ParametersFile};
+
+macro_rules! test_round_trip {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            let file = ParametersFile::new::<$params::GF>(&$params::PARAMS);
+            let json = serde_json::to_string(&file).unwrap();
+            assert_eq!(serde_json::from_str::<ParametersFile>(&json).unwrap(), file);
+            let toml = toml::to_string(&file).unwrap();
+            assert_eq!(toml::from_str::<ParametersFile>(&toml).unwrap(), file);
+
+            let prepared = file.to_prepared::<$params::GF>().unwrap();
+            assert_eq!(prepared.mds_matrix(), $params::prepared().mds_matrix());
+            assert_eq!(
+                prepared.round_constants(),
+                $params::prepared().round_constants()
+            );
+            assert_eq!(ParametersFile::from_prepared(&prepared), file);
+        }
+    };
+}
+
+test_round_trip!(test_round_trip_s128b, s128b);
+test_round_trip!(test_round_trip_sw2, sw2);
+test_round_trip!(test_round_trip_sw3, sw3);
+test_round_trip!(test_round_trip_sw4, sw4);
+test_round_trip!(test_round_trip_sw8, sw8);
+test_round_trip!(test_round_trip_pallas, pallas);
+test_round_trip!(test_round_trip_vesta, vesta);
+
+#[test]
+fn test_load_json() {
+    let json = format!(
+        r#"{{
+            "modulus": "0x800000000000011000000000000000000000000000000000000000000000001",
+            "alpha": 3,
+            "rate": 2,
+            "capacity": 1,
+            "output_size": 1,
+            "n_full_rounds": 8,
+            "n_partial_rounds": 83,
+            "mds_matrix": {},
+            "round_constants": {}
+        }}"#,
+        serde_json::to_string(sw2::PARAMS.mds_matrix).unwrap(),
+        serde_json::to_string(sw2::PARAMS.round_constants).unwrap(),
+    );
+    let file: ParametersFile = serde_json::from_str(&json).unwrap();
+    let prepared = file.to_prepared::<sw2::GF>().unwrap();
+    assert_eq!(prepared.mds_matrix(), sw2::prepared().mds_matrix());
+    assert_eq!(
+        file.to_prepared::<s128b::GF>().err(),
+        Some(ParametersError::ModulusMismatch)
+    );
+}
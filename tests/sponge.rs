@@ -0,0 +1,33 @@
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+use poseidon::{Sponge, SpongeOp};
+
+macro_rules! test_sponge {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            use $params::GF;
+            let rate = $params::PARAMS.rate as u32;
+            let io_pattern = [SpongeOp::Absorb(rate + 1), SpongeOp::Squeeze(rate + 1)];
+            let inputs: Vec<GF> = (0..(rate as u64 + 1)).map(GF::from).collect();
+
+            let mut sponge = Sponge::<GF>::start(&io_pattern, b"", &$params::PARAMS).unwrap();
+            sponge.absorb(&inputs).unwrap();
+            let output = sponge.squeeze(rate + 1).unwrap();
+            sponge.finish().unwrap();
+            assert_eq!(output.len(), rate as usize + 1);
+
+            let mut other = Sponge::<GF>::start(&io_pattern, b"other", &$params::PARAMS).unwrap();
+            other.absorb(&inputs).unwrap();
+            assert_ne!(other.squeeze(rate + 1).unwrap(), output);
+            other.finish().unwrap();
+        }
+    };
+}
+
+test_sponge!(test_sponge_s128b, s128b);
+test_sponge!(test_sponge_sw2, sw2);
+test_sponge!(test_sponge_sw3, sw3);
+test_sponge!(test_sponge_sw4, sw4);
+test_sponge!(test_sponge_sw8, sw8);
+test_sponge!(test_sponge_pallas, pallas);
+test_sponge!(test_sponge_vesta, vesta);
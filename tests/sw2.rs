@@ -1,5 +1,6 @@
 use poseidon::convert::felts_from_str;
 use poseidon::hash_sw2 as hash;
+use poseidon::hash_var_len_sw2 as hash_var_len;
 use poseidon::parameters::sw2::GF;
 
 #[test]
@@ -30,6 +31,19 @@ fn test_hash_double() {
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "2623443061182354919305685623110971845204520059843784147986720442625685837268",
+        "594741568659033195368801085041761499374896002161035154890469361989092940983",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
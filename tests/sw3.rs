@@ -1,5 +1,6 @@
 use poseidon::convert::felts_from_str;
 use poseidon::hash_sw3 as hash;
+use poseidon::hash_var_len_sw3 as hash_var_len;
 use poseidon::parameters::sw3::GF;
 
 #[test]
@@ -16,6 +17,20 @@ fn test_hash_simple() {
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "1223128461466570634623969182218124570509453444690658216199703366001293953850",
+        "3297413312288533523352887413662552599895401094341291166833958385575407064131",
+        "1934707028504925148821257521090734929948290468191832946602347414731936504724",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
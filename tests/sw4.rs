@@ -1,5 +1,6 @@
 use poseidon::convert::felts_from_str;
 use poseidon::hash_sw4 as hash;
+use poseidon::hash_var_len_sw4 as hash_var_len;
 use poseidon::parameters::sw4::GF;
 
 #[test]
@@ -17,6 +18,21 @@ fn test_hash_simple() {
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "1462563009959241986414142762445122749075922637107787992572301515677152863813",
+        "1130808453774941990434531088244004088332136347033070451260476337884065666873",
+        "399778263553643684515734154333817192365185236369665668439246550770557685730",
+        "2816386561520184425298342501560626615985391081859003225874265735979186318435",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
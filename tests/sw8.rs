@@ -1,5 +1,6 @@
 use poseidon::convert::felts_from_str;
 use poseidon::hash_sw8 as hash;
+use poseidon::hash_var_len_sw8 as hash_var_len;
 use poseidon::parameters::sw8::GF;
 
 #[test]
@@ -21,6 +22,25 @@ fn test_hash_simple() {
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "30940417331340376534436454363847177091487056173097959050221775788419546562",
+        "1996064826715656572084402407411743988388226960402044457877711751928140372896",
+        "2932684162414713073900121121402312691389982224190272075618235361327853979117",
+        "3455426574675182244066720284523485357980765258094677787007306097806825021739",
+        "2694921964687261210879576966663464151513993425685650672078247059293818151325",
+        "497603294500314065051025814611258404620970708664472679415381353270093939068",
+        "2012136406015847479621305719063875155269098114023663746235910925105357232018",
+        "2206911320711180500298595260475567137564870223513084153003301361810901368471",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {
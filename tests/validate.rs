@@ -0,0 +1,18 @@
+use poseidon::parameters::{pallas, s128b, sw2, sw3, sw4, sw8, vesta};
+
+macro_rules! test_validate {
+    ($name:ident, $params:ident) => {
+        #[test]
+        fn $name() {
+            assert_eq!($params::PARAMS.validate::<$params::GF>(), Ok(()));
+        }
+    };
+}
+
+test_validate!(test_validate_s128b, s128b);
+test_validate!(test_validate_sw2, sw2);
+test_validate!(test_validate_sw3, sw3);
+test_validate!(test_validate_sw4, sw4);
+test_validate!(test_validate_sw8, sw8);
+test_validate!(test_validate_pallas, pallas);
+test_validate!(test_validate_vesta, vesta);
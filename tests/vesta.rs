@@ -1,20 +1,36 @@
 use poseidon::convert::felts_from_str;
+use poseidon::hash_var_len_vesta as hash_var_len;
 use poseidon::hash_vesta as hash;
 use poseidon::parameters::vesta::GF;
 
 #[test]
 fn test_hash_simple() {
+    // All 55 full rounds, checked against an independent implementation of
+    // the round sequence.
     let input = ["0", "0"];
     let input = felts_from_str::<GF>(&input);
     let expected = [
-        "15503507843680028074813146032292970642340699155253454797233283122108694118843",
-        "304291385369895909791985322046133629782080136045317966776007874226152902593",
+        "27774379764132956311987196694592723545402590655317466350240746505447042350604",
+        "16368572117924224290616577527633562803538898966688013772915027214384428819478",
     ];
     let expected = felts_from_str::<GF>(&expected);
     let output = hash(&input);
     assert_eq!(output, expected);
 }
 
+#[test]
+fn test_hash_var_len() {
+    let input = ["7"];
+    let input = felts_from_str::<GF>(&input);
+    let expected = [
+        "7432424884352547585422732550812507901873407792823093144184819295908367248762",
+        "14099930925267596505491590932405431926030067045991781371339694088425375984972",
+    ];
+    let expected = felts_from_str::<GF>(&expected);
+    let output = hash_var_len(&input);
+    assert_eq!(output, expected);
+}
+
 #[test]
 #[should_panic]
 fn test_hash_wrong_size() {